@@ -0,0 +1,144 @@
+//! Compares placing discs with bitboard flips against walking every line from the placed disc
+//! and setting the flipped discs one at a time, as `Board` used to.
+
+use libreversi::{Board, Disc, Player, Position};
+use std::hint::black_box;
+use std::time::Instant;
+
+/// The old `place_piece_dry_run`: collect each line, then walk it again up to the anchor.
+fn walk_flips(board: &Board, pos: Position, player: Player) -> Vec<Position> {
+    let mut turned = vec![];
+    if board[pos] != Disc::Empty {
+        return turned;
+    }
+    for (neighbour_pos, disc) in board.neighbours(pos) {
+        if disc != player.opponent() {
+            continue;
+        }
+        let line: Vec<_> = board.get_points_in_line(pos, neighbour_pos).collect();
+        if let Some(anchor) = line.iter().position(|&(_, d)| d != player.opponent()) {
+            if line[anchor].1 == player {
+                turned.extend(
+                    line.iter()
+                        .take_while(|&&(_, d)| d == player.opponent())
+                        .map(|&(p, _)| p),
+                );
+            }
+        }
+    }
+    turned
+}
+
+/// The old `place_piece`: the dry run followed by one `set_piece` per square.
+fn walk_place(board: &mut Board, pos: Position, player: Player) -> Vec<Position> {
+    let turned = walk_flips(board, pos, player);
+    board.set_piece(pos, player.into());
+    for &p in &turned {
+        board.set_piece(p, player.into());
+    }
+    turned
+}
+
+/// Positions, with the side to move, from a few hundred pseudo-random games.
+fn positions() -> Vec<(Board, Player)> {
+    let mut seed = 0x2545_f491_4f6c_dd1du64;
+    let mut positions = vec![];
+    for _ in 0..200 {
+        let mut board = Board::default();
+        let mut player = Player::Player1;
+        loop {
+            let moves: Vec<_> = board.all_legal_moves(player).collect();
+            if moves.is_empty() {
+                player = player.opponent();
+                if board.legal_moves_mask(player) == 0 {
+                    break;
+                }
+                continue;
+            }
+            positions.push((board.clone(), player));
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            board.place_piece(moves[seed as usize % moves.len()], player);
+            player = player.opponent();
+        }
+    }
+    positions
+}
+
+/// Times `f` on every square of each position, or only on the legal moves if `legal_only`.
+fn time<F: FnMut(&(Board, Player), Position)>(
+    name: &str,
+    positions: &[(Board, Player)],
+    legal_only: bool,
+    mut f: F,
+) -> f64 {
+    let squares: Vec<Vec<Position>> = positions
+        .iter()
+        .map(|(board, player)| {
+            (0..64)
+                .map(|idx| Position::from_index(idx).unwrap())
+                .filter(|&pos| !legal_only || board.is_legal_move(pos, *player))
+                .collect()
+        })
+        .collect();
+    let start = Instant::now();
+    let mut calls = 0u64;
+    for _ in 0..5 {
+        for (position, squares) in positions.iter().zip(&squares) {
+            for &pos in squares {
+                f(position, pos);
+                calls += 1;
+            }
+        }
+    }
+    let ns = start.elapsed().as_nanos() as f64 / calls as f64;
+    println!("{:<32} {:>8.1} ns/call", name, ns);
+    ns
+}
+
+fn main() {
+    let positions = positions();
+    println!(
+        "{} positions, every square of each for the dry run, legal moves for placing\n",
+        positions.len()
+    );
+
+    let old = time(
+        "dry run, walking lines",
+        &positions,
+        false,
+        |(board, player), pos| {
+            black_box(walk_flips(board, pos, *player));
+        },
+    );
+    let new = time(
+        "dry run, bitboards",
+        &positions,
+        false,
+        |(board, player), pos| {
+            black_box(board.flips(pos, *player));
+        },
+    );
+    println!("{:<32} {:>8.1}x\n", "speedup", old / new);
+
+    let old = time(
+        "place, walking lines",
+        &positions,
+        true,
+        |(board, player), pos| {
+            let mut board = board.clone();
+            black_box(walk_place(&mut board, pos, *player));
+        },
+    );
+    let new = time(
+        "place, bitboards",
+        &positions,
+        true,
+        |(board, player), pos| {
+            let mut board = board.clone();
+            black_box(board.place_piece(pos, *player));
+        },
+    );
+    println!("{:<32} {:>8.1}x", "speedup", old / new);
+}
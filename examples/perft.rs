@@ -0,0 +1,20 @@
+use libreversi::{perft, Board, Player};
+use std::time::Instant;
+
+fn main() {
+    let max_depth = std::env::args()
+        .nth(1)
+        .map(|arg| arg.parse().expect("depth must be a non-negative integer"))
+        .unwrap_or(8);
+    let board = Board::default();
+    for depth in 1..=max_depth {
+        let start = Instant::now();
+        let nodes = perft(&board, Player::Player1, depth);
+        println!(
+            "perft({:>2}) = {:>12} ({:.3}s)",
+            depth,
+            nodes,
+            start.elapsed().as_secs_f64()
+        );
+    }
+}
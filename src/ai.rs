@@ -0,0 +1,598 @@
+//! Computer opponents.
+
+pub mod eval;
+pub mod mcts;
+pub mod tt;
+
+use crate::board::{Board, Player};
+use crate::game::Ply;
+use crate::position::Position;
+pub use eval::{DiscDifference, Evaluator};
+pub use mcts::{MctsSearcher, Playout};
+use std::sync::atomic::{AtomicBool, Ordering as AtomicOrdering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+pub use tt::TranspositionTable;
+
+use tt::{Bound, Entry};
+
+/// Score of a finished game won by the side to move, before adding the final disc difference.
+pub const WIN_SCORE: i32 = 1_000_000;
+
+const INFINITY: i32 = WIN_SCORE + 100;
+
+/// Number of entries in a searcher's transposition table unless set otherwise
+pub const DEFAULT_TABLE_SIZE: usize = 1 << 16;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchResult {
+    /// The move to play, or `None` if the side to move has to pass or the game is over
+    pub best_move: Option<Position>,
+    /// Score from the point of view of the side to move
+    pub score: i32,
+    /// The expected continuation, starting with `best_move`. It may stop short of the search
+    /// depth where the rest of the line came from the transposition table.
+    pub pv: Vec<Ply>,
+    /// Depth of the last completed iteration, which produced this result
+    pub depth: u32,
+    /// Number of positions visited over all iterations
+    pub nodes: u64,
+}
+
+/// When a search has to stop. The search deepens one ply at a time until `max_depth` is
+/// reached or one of the other limits runs out, in which case the result of the last
+/// completed iteration is returned.
+#[derive(Debug, Clone)]
+pub struct SearchLimits {
+    pub max_depth: u32,
+    pub max_nodes: Option<u64>,
+    pub time_budget: Option<Duration>,
+    /// Lets another thread stop the search by setting the flag
+    pub stop_flag: Option<Arc<AtomicBool>>,
+}
+
+impl SearchLimits {
+    pub fn depth(max_depth: u32) -> Self {
+        Self {
+            max_depth,
+            ..Self::default()
+        }
+    }
+
+    pub fn time(time_budget: Duration) -> Self {
+        Self {
+            time_budget: Some(time_budget),
+            ..Self::default()
+        }
+    }
+}
+
+impl Default for SearchLimits {
+    /// No limit other than the length of a game.
+    fn default() -> Self {
+        Self {
+            max_depth: 64,
+            max_nodes: None,
+            time_budget: None,
+            stop_flag: None,
+        }
+    }
+}
+
+/// Iterative deepening negamax search with alpha-beta pruning, scoring the leaves with `E`.
+///
+/// Results are cached in a transposition table that is kept between searches.
+#[derive(Debug, Clone)]
+pub struct Searcher<E = DiscDifference> {
+    limits: SearchLimits,
+    evaluator: E,
+    table: TranspositionTable,
+    nodes: u64,
+    deadline: Option<Instant>,
+    aborted: bool,
+    /// Depth of the last completed iteration of the current search
+    completed_depth: u32,
+    /// Whether the current iteration cut any line short at its depth limit
+    hit_horizon: bool,
+}
+
+impl Searcher {
+    pub fn new(depth: u32) -> Self {
+        Self::with_evaluator(depth, DiscDifference)
+    }
+}
+
+impl<E: Evaluator> Searcher<E> {
+    pub fn with_evaluator(depth: u32, evaluator: E) -> Self {
+        Self {
+            limits: SearchLimits::depth(depth),
+            evaluator,
+            table: TranspositionTable::new(DEFAULT_TABLE_SIZE),
+            nodes: 0,
+            deadline: None,
+            aborted: false,
+            completed_depth: 0,
+            hit_horizon: false,
+        }
+    }
+
+    pub fn evaluator(&self) -> &E {
+        &self.evaluator
+    }
+
+    pub fn table(&self) -> &TranspositionTable {
+        &self.table
+    }
+
+    pub fn table_mut(&mut self) -> &mut TranspositionTable {
+        &mut self.table
+    }
+
+    pub fn depth(&self) -> u32 {
+        self.limits.max_depth
+    }
+
+    pub fn set_depth(&mut self, depth: u32) {
+        self.limits.max_depth = depth;
+    }
+
+    pub fn limits(&self) -> &SearchLimits {
+        &self.limits
+    }
+
+    pub fn set_limits(&mut self, limits: SearchLimits) {
+        self.limits = limits;
+    }
+
+    pub fn search(&mut self, board: &Board, player: Player) -> SearchResult {
+        self.nodes = 0;
+        self.aborted = false;
+        self.completed_depth = 0;
+        self.deadline = self
+            .limits
+            .time_budget
+            .map(|budget| Instant::now() + budget);
+        self.table.new_search();
+
+        // Until an iteration completes, fall back to any legal move
+        let mut result = SearchResult {
+            best_move: board.all_legal_moves(player).next(),
+            score: 0,
+            pv: vec![],
+            depth: 0,
+            nodes: 0,
+        };
+        // Moves are made and taken back on this one board instead of cloning it at every node
+        let mut board = board.clone();
+        for depth in 1..=self.limits.max_depth {
+            // Once there is a result, a stop or the deadline rules out the next iteration
+            if depth > 1 && self.stop_requested() {
+                break;
+            }
+            let mut pv = vec![];
+            self.hit_horizon = false;
+            let score = self.negamax(&mut board, player, depth, 0, -INFINITY, INFINITY, &mut pv);
+            if self.aborted {
+                break;
+            }
+            result.best_move = match pv.first() {
+                Some(&Ply::Move(_, pos)) => Some(pos),
+                _ => None,
+            };
+            result.score = score;
+            result.pv = pv;
+            result.depth = depth;
+            self.completed_depth = depth;
+            // Searching deeper changes nothing once every line ends before the horizon
+            if !self.hit_horizon {
+                break;
+            }
+        }
+        result.nodes = self.nodes;
+        result
+    }
+
+    /// Whether the deadline has passed or another thread has set the stop flag.
+    fn stop_requested(&self) -> bool {
+        if let Some(deadline) = self.deadline {
+            if Instant::now() >= deadline {
+                return true;
+            }
+        }
+        match &self.limits.stop_flag {
+            Some(flag) => flag.load(AtomicOrdering::Relaxed),
+            None => false,
+        }
+    }
+
+    fn out_of_budget(&mut self, ply: u32) -> bool {
+        if self.aborted {
+            return true;
+        }
+        if let Some(max_nodes) = self.limits.max_nodes {
+            if self.nodes >= max_nodes {
+                self.aborted = true;
+            }
+        }
+        // Polled every 1024 nodes, and before each root move once an iteration has completed
+        let root_move = ply == 1 && self.completed_depth > 0;
+        if (self.nodes & 1023 == 0 || root_move) && self.stop_requested() {
+            self.aborted = true;
+        }
+        self.aborted
+    }
+
+    /// The evaluator's score, kept strictly between the scores of a lost and a won game so
+    /// that it fits the search window.
+    fn evaluate(&self, board: &Board, player: Player) -> i32 {
+        self.evaluator
+            .evaluate(board, player)
+            .clamp(1 - WIN_SCORE, WIN_SCORE - 1)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn negamax(
+        &mut self,
+        board: &mut Board,
+        player: Player,
+        depth: u32,
+        ply: u32,
+        mut alpha: i32,
+        beta: i32,
+        pv: &mut Vec<Ply>,
+    ) -> i32 {
+        self.nodes += 1;
+        pv.clear();
+        // The root always gets to look at its moves, so a completed iteration has a best move
+        if ply > 0 && self.out_of_budget(ply) {
+            return 0;
+        }
+        if board.legal_moves_mask(player) == 0 {
+            let opponent = player.opponent();
+            if board.legal_moves_mask(opponent) == 0 {
+                return final_score(board, player);
+            }
+            if depth == 0 {
+                self.hit_horizon = true;
+                return self.evaluate(board, player);
+            }
+            let mut line = vec![];
+            let score = -self.negamax(
+                board,
+                opponent,
+                depth - 1,
+                ply + 1,
+                -beta,
+                -alpha,
+                &mut line,
+            );
+            pv.push(Ply::Pass(player));
+            pv.append(&mut line);
+            return score;
+        }
+        if depth == 0 {
+            self.hit_horizon = true;
+            return self.evaluate(board, player);
+        }
+
+        let key = board.zobrist_key(player);
+        let mut moves: Vec<Position> = board.all_legal_moves(player).collect();
+        if let Some(entry) = self.table.probe(key) {
+            // Never cut off at the root, where a best move and its line are needed
+            if entry.depth >= depth && ply > 0 {
+                let cutoff = match entry.bound {
+                    Bound::Exact => true,
+                    Bound::Lower => entry.score >= beta,
+                    Bound::Upper => entry.score <= alpha,
+                };
+                if cutoff {
+                    self.hit_horizon = true;
+                    pv.extend(entry.best_move.map(|pos| Ply::Move(player, pos)));
+                    return entry.score;
+                }
+            }
+            // The best move found last time, usually by the previous iteration, goes first
+            if let Some(i) = moves.iter().position(|&pos| Some(pos) == entry.best_move) {
+                moves[..=i].rotate_right(1);
+            }
+        }
+
+        let alpha_orig = alpha;
+        let mut best = -INFINITY;
+        let mut best_move = None;
+        let mut line = vec![];
+        for pos in moves {
+            let mv = board.place_piece(pos, player);
+            let score = -self.negamax(
+                board,
+                player.opponent(),
+                depth - 1,
+                ply + 1,
+                -beta,
+                -alpha,
+                &mut line,
+            );
+            board.unmake(&mv);
+            if self.aborted {
+                return 0;
+            }
+            if score > best {
+                best = score;
+                best_move = Some(pos);
+                pv.clear();
+                pv.push(Ply::Move(player, pos));
+                pv.append(&mut line);
+            }
+            if best > alpha {
+                alpha = best;
+            }
+            if alpha >= beta {
+                break;
+            }
+        }
+
+        let bound = if best <= alpha_orig {
+            Bound::Upper
+        } else if best >= beta {
+            Bound::Lower
+        } else {
+            Bound::Exact
+        };
+        self.table
+            .store(Entry::new(key, depth, bound, best, best_move));
+        best
+    }
+}
+
+/// Score of a finished game: a win or loss dominates any heuristic score, and larger margins
+/// are preferred.
+pub(crate) fn final_score(board: &Board, player: Player) -> i32 {
+    let diff = DiscDifference.evaluate(board, player);
+    diff.signum() * WIN_SCORE + diff
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::Disc;
+    use eval::{Corners, Mobility, Weighted};
+
+    fn evaluate(board: &Board, player: Player) -> i32 {
+        DiscDifference.evaluate(board, player)
+    }
+
+    /// Plain minimax without pruning, to check that alpha-beta finds the same score
+    fn minimax(board: &Board, player: Player, depth: u32) -> i32 {
+        let opponent = player.opponent();
+        if board.legal_moves_mask(player) == 0 {
+            if board.legal_moves_mask(opponent) == 0 {
+                return final_score(board, player);
+            }
+            if depth == 0 {
+                return evaluate(board, player);
+            }
+            return -minimax(board, opponent, depth - 1);
+        }
+        if depth == 0 {
+            return evaluate(board, player);
+        }
+        board
+            .all_legal_moves(player)
+            .map(|pos| {
+                let mut child = board.clone();
+                child.place_piece(pos, player);
+                -minimax(&child, opponent, depth - 1)
+            })
+            .max()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_matches_minimax() {
+        let mut board = Board::default();
+        let mut player = Player::Player1;
+        for _ in 0..6 {
+            for depth in 1..=4 {
+                let result = Searcher::new(depth).search(&board, player);
+                assert_eq!(result.score, minimax(&board, player, depth));
+            }
+            let pos = board.all_legal_moves(player).last().unwrap();
+            board.place_piece(pos, player);
+            player = player.opponent();
+        }
+    }
+
+    #[test]
+    fn test_transposition_table() {
+        let board = Board::default();
+        let mut searcher = Searcher::new(5);
+        let first = searcher.search(&board, Player::Player1);
+        assert!(!searcher.table().is_empty());
+        let entry = searcher
+            .table()
+            .probe(board.zobrist_key(Player::Player1))
+            .unwrap();
+        assert_eq!(entry.depth, 5);
+        assert_eq!(entry.best_move, first.best_move);
+
+        // The second search is answered mostly from the table
+        let second = searcher.search(&board, Player::Player1);
+        assert_eq!(second.score, first.score);
+        assert_eq!(second.best_move, first.best_move);
+        assert!(second.nodes < first.nodes);
+
+        searcher.table_mut().clear();
+        assert!(searcher.table().is_empty());
+        assert_eq!(searcher.search(&board, Player::Player1).nodes, first.nodes);
+    }
+
+    #[test]
+    fn test_node_limit() {
+        let board = Board::default();
+        let mut searcher = Searcher::new(20);
+        searcher.set_limits(SearchLimits {
+            max_nodes: Some(2000),
+            ..SearchLimits::depth(20)
+        });
+        let result = searcher.search(&board, Player::Player1);
+        assert!(result.nodes <= 2000);
+        assert!(result.depth >= 1 && result.depth < 20);
+        assert!(board.is_legal_move(result.best_move.unwrap(), Player::Player1));
+
+        // The result is the one a plain search to the completed depth finds
+        let fixed = Searcher::new(result.depth).search(&board, Player::Player1);
+        assert_eq!(fixed.score, result.score);
+        assert_eq!(fixed.best_move, result.best_move);
+    }
+
+    #[test]
+    fn test_time_budget() {
+        let board = Board::default();
+        let mut searcher = Searcher::new(0);
+        searcher.set_limits(SearchLimits::time(Duration::from_millis(50)));
+        let start = Instant::now();
+        let result = searcher.search(&board, Player::Player1);
+        assert!(start.elapsed() < Duration::from_secs(5));
+        assert!(result.depth >= 1 && result.depth < 64);
+        assert!(board.is_legal_move(result.best_move.unwrap(), Player::Player1));
+    }
+
+    #[test]
+    fn test_stop_flag() {
+        let board = Board::default();
+        let flag = Arc::new(AtomicBool::new(true));
+        let mut searcher = Searcher::new(0);
+        searcher.set_limits(SearchLimits {
+            stop_flag: Some(flag.clone()),
+            ..SearchLimits::default()
+        });
+        let result = searcher.search(&board, Player::Player1);
+        assert!(result.depth < 64);
+        assert!(board.is_legal_move(result.best_move.unwrap(), Player::Player1));
+
+        flag.store(false, AtomicOrdering::Relaxed);
+        searcher.set_depth(3);
+        assert_eq!(searcher.search(&board, Player::Player1).depth, 3);
+    }
+
+    #[test]
+    fn test_preset_stop_returns_after_depth_one() {
+        let board = Board::default();
+        let depth_one = Searcher::new(1).search(&board, Player::Player1);
+        let stopped = SearchLimits {
+            stop_flag: Some(Arc::new(AtomicBool::new(true))),
+            ..SearchLimits::default()
+        };
+        for limits in &[stopped, SearchLimits::time(Duration::from_secs(0))] {
+            let mut searcher = Searcher::new(0);
+            searcher.set_limits(limits.clone());
+            let result = searcher.search(&board, Player::Player1);
+            assert_eq!(result.depth, 1);
+            assert_eq!(result.nodes, depth_one.nodes);
+            assert_eq!(result.best_move, depth_one.best_move);
+        }
+    }
+
+    #[test]
+    fn test_stops_deepening_at_game_end() {
+        let mut board = Board::empty();
+        board.set_piece(Position::at(0, 0), Disc::Player1);
+        board.set_piece(Position::at(0, 1), Disc::Player2);
+        let mut searcher = Searcher::new(0);
+        searcher.set_limits(SearchLimits::default());
+        let result = searcher.search(&board, Player::Player1);
+        assert_eq!(result.depth, 1);
+        assert_eq!(result.best_move, Some(Position::at(0, 2)));
+        assert_eq!(result.score, WIN_SCORE + 3);
+    }
+
+    #[test]
+    fn test_custom_evaluator() {
+        let board = Board::default();
+        let evaluator = Weighted::new().with(5, Mobility).with(100, Corners);
+        let mut searcher = Searcher::with_evaluator(2, evaluator);
+        let result = searcher.search(&board, Player::Player1);
+        assert!(result.best_move.is_some());
+        assert_eq!(result.pv.len(), 2);
+
+        let mut replay = board.clone();
+        for ply in &result.pv {
+            if let Ply::Move(player, pos) = *ply {
+                replay.place_piece(pos, player);
+            }
+        }
+        assert_eq!(
+            searcher.evaluator().evaluate(&replay, Player::Player1),
+            result.score
+        );
+    }
+
+    #[test]
+    fn test_evaluation_kept_inside_window() {
+        #[derive(Debug)]
+        struct Extreme;
+
+        impl Evaluator for Extreme {
+            fn evaluate(&self, board: &Board, player: Player) -> i32 {
+                if DiscDifference.evaluate(board, player) > 0 {
+                    i32::MAX
+                } else {
+                    i32::MIN
+                }
+            }
+        }
+
+        let board = Board::default();
+        let result = Searcher::with_evaluator(1, Extreme).search(&board, Player::Player1);
+        // Every reply leaves player 2 behind, which would overflow when negated
+        assert!(result.best_move.is_some());
+        assert_eq!(result.score, WIN_SCORE - 1);
+    }
+
+    #[test]
+    fn test_principal_variation() {
+        let board = Board::default();
+        let result = Searcher::new(3).search(&board, Player::Player1);
+        assert_eq!(result.pv.len(), 3);
+        assert_eq!(
+            result.pv[0],
+            Ply::Move(Player::Player1, result.best_move.unwrap())
+        );
+
+        // Replaying the PV reaches a leaf with the reported score
+        let mut replay = board.clone();
+        for ply in &result.pv {
+            if let Ply::Move(player, pos) = *ply {
+                assert!(replay.try_place_piece(pos, player).is_ok());
+            }
+        }
+        assert_eq!(-evaluate(&replay, Player::Player2), result.score);
+        assert!(result.nodes > 1);
+    }
+
+    #[test]
+    fn test_handles_passes() {
+        // Player 2 must pass, after which player 1 wins on c1
+        let mut board = Board::empty();
+        board.set_piece(Position::at(0, 0), Disc::Player1);
+        board.set_piece(Position::at(0, 1), Disc::Player2);
+        let result = Searcher::new(3).search(&board, Player::Player2);
+        assert_eq!(result.best_move, None);
+        assert_eq!(
+            result.pv,
+            vec![
+                Ply::Pass(Player::Player2),
+                Ply::Move(Player::Player1, Position::at(0, 2))
+            ]
+        );
+        assert_eq!(result.score, -(WIN_SCORE + 3));
+    }
+
+    #[test]
+    fn test_game_over() {
+        let result = Searcher::new(3).search(&Board::empty(), Player::Player1);
+        assert_eq!(result.best_move, None);
+        assert!(result.pv.is_empty());
+        assert_eq!(result.score, 0);
+    }
+}
@@ -0,0 +1,346 @@
+//! Static evaluation of positions.
+//!
+//! Every evaluator scores a position from the point of view of `player`: positive scores
+//! favour `player`, negative ones their opponent.
+
+use super::WIN_SCORE;
+use crate::board::bitboard::adjacent;
+use crate::board::{Board, Player};
+use std::fmt;
+
+const CORNERS: u64 = 1 | 1 << 7 | 1 << 56 | 1 << 63;
+
+/// For each corner, the corner itself and its X-square and two C-squares
+const CORNER_REGIONS: [(u64, u64, u64); 4] = [
+    (1, 1 << 9, 1 << 1 | 1 << 8),
+    (1 << 7, 1 << 14, 1 << 6 | 1 << 15),
+    (1 << 56, 1 << 49, 1 << 48 | 1 << 57),
+    (1 << 63, 1 << 54, 1 << 55 | 1 << 62),
+];
+
+/// Scores should stay strictly between `-WIN_SCORE` and [`WIN_SCORE`], which are kept for
+/// finished games. The [`Searcher`](super::Searcher) clamps anything outside that range.
+pub trait Evaluator {
+    fn evaluate(&self, board: &Board, player: Player) -> i32;
+}
+
+impl<E: Evaluator + ?Sized> Evaluator for &E {
+    fn evaluate(&self, board: &Board, player: Player) -> i32 {
+        (**self).evaluate(board, player)
+    }
+}
+
+impl<E: Evaluator + ?Sized> Evaluator for Box<E> {
+    fn evaluate(&self, board: &Board, player: Player) -> i32 {
+        (**self).evaluate(board, player)
+    }
+}
+
+fn difference(own: u64, opponent: u64) -> i32 {
+    own.count_ones() as i32 - opponent.count_ones() as i32
+}
+
+/// Number of own discs minus number of opponent discs.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct DiscDifference;
+
+impl Evaluator for DiscDifference {
+    fn evaluate(&self, board: &Board, player: Player) -> i32 {
+        difference(board.discs(player), board.discs(player.opponent()))
+    }
+}
+
+/// Number of own legal moves minus number of opponent legal moves.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct Mobility;
+
+impl Evaluator for Mobility {
+    fn evaluate(&self, board: &Board, player: Player) -> i32 {
+        difference(
+            board.legal_moves_mask(player),
+            board.legal_moves_mask(player.opponent()),
+        )
+    }
+}
+
+/// Empty squares next to an opponent disc minus empty squares next to an own disc, i.e. the
+/// squares each side might be able to move to later on.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct PotentialMobility;
+
+impl Evaluator for PotentialMobility {
+    fn evaluate(&self, board: &Board, player: Player) -> i32 {
+        let empty = board.empty_squares();
+        difference(
+            adjacent(board.discs(player.opponent())) & empty,
+            adjacent(board.discs(player)) & empty,
+        )
+    }
+}
+
+/// Number of own corners minus number of opponent corners.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct Corners;
+
+impl Evaluator for Corners {
+    fn evaluate(&self, board: &Board, player: Player) -> i32 {
+        difference(
+            board.discs(player) & CORNERS,
+            board.discs(player.opponent()) & CORNERS,
+        )
+    }
+}
+
+/// Penalty for discs on the X-squares (worth 2) and C-squares (worth 1) next to an empty
+/// corner, which tend to hand that corner to the opponent.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct XCSquares;
+
+impl Evaluator for XCSquares {
+    fn evaluate(&self, board: &Board, player: Player) -> i32 {
+        let own = board.discs(player);
+        let opponent = board.discs(player.opponent());
+        let empty = board.empty_squares();
+        CORNER_REGIONS
+            .iter()
+            .filter(|&&(corner, _, _)| empty & corner != 0)
+            .map(|&(_, x, c)| {
+                2 * difference(opponent & x, own & x) + difference(opponent & c, own & c)
+            })
+            .sum()
+    }
+}
+
+/// Opponent frontier discs minus own frontier discs, where a frontier disc is one next to
+/// an empty square.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct Frontier;
+
+impl Evaluator for Frontier {
+    fn evaluate(&self, board: &Board, player: Player) -> i32 {
+        let frontier = adjacent(board.empty_squares());
+        difference(
+            board.discs(player.opponent()) & frontier,
+            board.discs(player) & frontier,
+        )
+    }
+}
+
+/// Sum of the weights of own squares minus the weights of opponent squares, indexed by
+/// [`Position::index`](crate::Position::index).
+#[derive(Debug, Copy, Clone)]
+pub struct SquareWeights(pub [i32; 64]);
+
+impl Default for SquareWeights {
+    #[rustfmt::skip]
+    fn default() -> Self {
+        SquareWeights([
+            100, -20,  10,   5,   5,  10, -20, 100,
+            -20, -50,  -2,  -2,  -2,  -2, -50, -20,
+             10,  -2,  -1,  -1,  -1,  -1,  -2,  10,
+              5,  -2,  -1,  -1,  -1,  -1,  -2,   5,
+              5,  -2,  -1,  -1,  -1,  -1,  -2,   5,
+             10,  -2,  -1,  -1,  -1,  -1,  -2,  10,
+            -20, -50,  -2,  -2,  -2,  -2, -50, -20,
+            100, -20,  10,   5,   5,  10, -20, 100,
+        ])
+    }
+}
+
+impl Evaluator for SquareWeights {
+    fn evaluate(&self, board: &Board, player: Player) -> i32 {
+        let own = board.discs(player);
+        let opponent = board.discs(player.opponent());
+        self.0
+            .iter()
+            .enumerate()
+            .map(|(idx, &weight)| {
+                let bit = 1 << idx;
+                if own & bit != 0 {
+                    weight
+                } else if opponent & bit != 0 {
+                    -weight
+                } else {
+                    0
+                }
+            })
+            .sum()
+    }
+}
+
+/// A weighted sum of other evaluators.
+///
+/// The sum saturates instead of overflowing and is kept strictly between the scores of a
+/// lost and a won game, so large weights never make a position look decided.
+///
+/// ```
+/// use libreversi::ai::eval::{Corners, Mobility, Weighted};
+///
+/// let evaluator = Weighted::new().with(10, Mobility).with(50, Corners);
+/// ```
+#[derive(Default)]
+pub struct Weighted {
+    terms: Vec<(i32, Box<dyn Evaluator>)>,
+}
+
+impl Weighted {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with<E: Evaluator + 'static>(mut self, weight: i32, evaluator: E) -> Self {
+        self.terms.push((weight, Box::new(evaluator)));
+        self
+    }
+}
+
+impl fmt::Debug for Weighted {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let weights: Vec<i32> = self.terms.iter().map(|&(weight, _)| weight).collect();
+        f.debug_struct("Weighted")
+            .field("weights", &weights)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Evaluator for Weighted {
+    fn evaluate(&self, board: &Board, player: Player) -> i32 {
+        self.terms
+            .iter()
+            .fold(0i32, |sum, (weight, evaluator)| {
+                sum.saturating_add(weight.saturating_mul(evaluator.evaluate(board, player)))
+            })
+            .clamp(1 - WIN_SCORE, WIN_SCORE - 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::Disc;
+    use crate::position::Position;
+
+    fn board_with(p1: &[(usize, usize)], p2: &[(usize, usize)]) -> Board {
+        let mut board = Board::empty();
+        for &(row, col) in p1 {
+            board.set_piece(Position::at(row, col), Disc::Player1);
+        }
+        for &(row, col) in p2 {
+            board.set_piece(Position::at(row, col), Disc::Player2);
+        }
+        board
+    }
+
+    /// Every stock evaluator is zero-sum between the two players
+    fn assert_antisymmetric<E: Evaluator>(evaluator: &E, board: &Board) {
+        assert_eq!(
+            evaluator.evaluate(board, Player::Player1),
+            -evaluator.evaluate(board, Player::Player2)
+        );
+    }
+
+    #[test]
+    fn test_start_position_is_balanced() {
+        let board = Board::default();
+        let evaluators: Vec<Box<dyn Evaluator>> = vec![
+            Box::new(DiscDifference),
+            Box::new(Mobility),
+            Box::new(PotentialMobility),
+            Box::new(Corners),
+            Box::new(XCSquares),
+            Box::new(Frontier),
+            Box::new(SquareWeights::default()),
+        ];
+        for evaluator in &evaluators {
+            assert_eq!(evaluator.evaluate(&board, Player::Player1), 0);
+        }
+    }
+
+    #[test]
+    fn test_disc_difference() {
+        let mut board = Board::default();
+        board.place_piece(Position::at(2, 3), Player::Player1);
+        assert_eq!(DiscDifference.evaluate(&board, Player::Player1), 3);
+        assert_antisymmetric(&DiscDifference, &board);
+    }
+
+    #[test]
+    fn test_mobility() {
+        let mut board = Board::default();
+        board.place_piece(Position::at(2, 3), Player::Player1);
+        let p1 = board.legal_moves_mask(Player::Player1).count_ones() as i32;
+        let p2 = board.legal_moves_mask(Player::Player2).count_ones() as i32;
+        assert_eq!(Mobility.evaluate(&board, Player::Player1), p1 - p2);
+        assert_antisymmetric(&Mobility, &board);
+    }
+
+    #[test]
+    fn test_potential_mobility() {
+        // A lone player 2 disc in the corner leaves three empty squares next to it
+        let board = board_with(&[(4, 4)], &[(0, 0)]);
+        assert_eq!(PotentialMobility.evaluate(&board, Player::Player1), 3 - 8);
+        assert_antisymmetric(&PotentialMobility, &board);
+    }
+
+    #[test]
+    fn test_corners() {
+        let board = board_with(&[(0, 0), (7, 7)], &[(0, 7)]);
+        assert_eq!(Corners.evaluate(&board, Player::Player1), 1);
+        assert_antisymmetric(&Corners, &board);
+    }
+
+    #[test]
+    fn test_xc_squares() {
+        // b2 next to an empty a1 is penalised, g2 next to an occupied h1 is not
+        let board = board_with(&[(1, 1), (1, 6), (0, 7)], &[(0, 6)]);
+        assert_eq!(XCSquares.evaluate(&board, Player::Player1), -2);
+        let board = board_with(&[(0, 1)], &[(1, 0), (6, 6)]);
+        assert_eq!(XCSquares.evaluate(&board, Player::Player1), 2);
+        assert_antisymmetric(&XCSquares, &board);
+    }
+
+    #[test]
+    fn test_frontier() {
+        let mut board = Board::default();
+        assert_eq!(Frontier.evaluate(&board, Player::Player1), 0);
+        board.place_piece(Position::at(2, 3), Player::Player1);
+        assert_eq!(Frontier.evaluate(&board, Player::Player1), 1 - 4);
+        assert_antisymmetric(&Frontier, &board);
+    }
+
+    #[test]
+    fn test_square_weights() {
+        let board = board_with(&[(0, 0), (1, 1)], &[(0, 1)]);
+        assert_eq!(
+            SquareWeights::default().evaluate(&board, Player::Player1),
+            100 - 50 + 20
+        );
+        assert_antisymmetric(&SquareWeights::default(), &board);
+    }
+
+    #[test]
+    fn test_weighted() {
+        let board = board_with(&[(0, 0), (7, 7)], &[(0, 7), (3, 3), (3, 4)]);
+        let evaluator = Weighted::new()
+            .with(1, DiscDifference)
+            .with(10, Corners)
+            .with(-3, Corners);
+        assert_eq!(evaluator.evaluate(&board, Player::Player1), -1 + 10 - 3);
+        assert_eq!(Weighted::new().evaluate(&board, Player::Player1), 0);
+    }
+
+    #[test]
+    fn test_weighted_saturates() {
+        // Player 1 is three discs ahead, so the first term alone overflows an i32
+        let board = board_with(&[(0, 0), (0, 1), (0, 2)], &[]);
+        let evaluator = Weighted::new()
+            .with(i32::MAX, DiscDifference)
+            .with(i32::MAX, DiscDifference);
+        assert_eq!(evaluator.evaluate(&board, Player::Player1), WIN_SCORE - 1);
+        assert_eq!(evaluator.evaluate(&board, Player::Player2), 1 - WIN_SCORE);
+        assert_eq!(
+            format!("{:?}", evaluator),
+            format!("Weighted {{ weights: [{0}, {0}], .. }}", i32::MAX)
+        );
+    }
+}
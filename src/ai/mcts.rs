@@ -0,0 +1,579 @@
+//! Monte Carlo tree search.
+//!
+//! The tree is grown one node per playout and moves are picked with UCT, which balances the
+//! win rate of a move against how rarely it has been tried. No evaluation is needed: the
+//! playouts are played out to the end of the game, either at random or guided by an
+//! [`Evaluator`].
+
+use super::{SearchLimits, SearchResult, WIN_SCORE};
+use crate::ai::eval::{Evaluator, SquareWeights};
+use crate::board::bitboard::Bits;
+use crate::board::{Board, Player};
+use crate::game::Ply;
+use crate::position::Position;
+use crate::rng::Rng;
+use std::collections::VecDeque;
+use std::sync::atomic::Ordering as AtomicOrdering;
+use std::time::Instant;
+
+/// Exploration constant of UCT unless set otherwise, roughly `sqrt(2)`
+pub const DEFAULT_EXPLORATION: f64 = 1.4;
+
+/// Number of playouts per search of a default searcher
+pub const DEFAULT_PLAYOUTS: u64 = 10_000;
+
+/// How moves are chosen once a playout leaves the tree.
+#[derive(Debug, Clone)]
+pub enum Playout<E = SquareWeights> {
+    /// Uniformly at random among the legal moves
+    Random,
+    /// The move after which `E` scores the position best for the mover, breaking ties at
+    /// random
+    Guided(E),
+}
+
+#[derive(Debug, Clone)]
+struct Node {
+    board: Board,
+    to_move: Player,
+    /// The ply that led here from the parent, `None` at the root
+    ply: Option<Ply>,
+    parent: Option<usize>,
+    children: Vec<usize>,
+    /// Moves that have no child yet
+    untried: u64,
+    /// Whether `to_move` has to pass and the pass has no child yet
+    untried_pass: bool,
+    visits: u32,
+    /// Sum of the playout results for the player who played `ply`, 1 for a win and 0.5 for a
+    /// draw
+    reward: f64,
+}
+
+impl Node {
+    fn new(board: Board, to_move: Player, ply: Option<Ply>, parent: Option<usize>) -> Self {
+        let untried = board.legal_moves_mask(to_move);
+        let untried_pass = untried == 0 && board.legal_moves_mask(to_move.opponent()) != 0;
+        Self {
+            board,
+            to_move,
+            ply,
+            parent,
+            children: vec![],
+            untried,
+            untried_pass,
+            visits: 0,
+            reward: 0.0,
+        }
+    }
+
+    fn is_expanded(&self) -> bool {
+        self.untried == 0 && !self.untried_pass
+    }
+
+    /// The player whose results `reward` counts
+    fn mover(&self) -> Player {
+        self.to_move.opponent()
+    }
+}
+
+/// Monte Carlo tree search with UCT.
+///
+/// `max_nodes` of the [`SearchLimits`] bounds the number of playouts and `max_depth` is
+/// ignored. Limits with no playout count, time budget or stop flag run [`DEFAULT_PLAYOUTS`]
+/// playouts. The tree is kept between searches, so when the next search starts from a
+/// position a move or two further down the game, the playouts spent on it are reused.
+#[derive(Debug, Clone)]
+pub struct MctsSearcher<E = SquareWeights> {
+    limits: SearchLimits,
+    exploration: f64,
+    playout: Playout<E>,
+    rng: Rng,
+    nodes: Vec<Node>,
+}
+
+impl Default for MctsSearcher {
+    fn default() -> Self {
+        Self::new(DEFAULT_PLAYOUTS)
+    }
+}
+
+impl MctsSearcher {
+    /// Creates a searcher with random playouts.
+    pub fn new(playouts: u64) -> Self {
+        Self::with_playout(playouts, Playout::Random)
+    }
+}
+
+impl<E: Evaluator> MctsSearcher<E> {
+    pub fn with_playout(playouts: u64, playout: Playout<E>) -> Self {
+        Self {
+            limits: SearchLimits {
+                max_nodes: Some(playouts),
+                ..SearchLimits::default()
+            },
+            exploration: DEFAULT_EXPLORATION,
+            playout,
+            rng: Rng::new(0),
+            nodes: vec![],
+        }
+    }
+
+    pub fn exploration(&self) -> f64 {
+        self.exploration
+    }
+
+    /// Higher values spread the playouts over more moves, lower values focus them on the
+    /// moves that have done best so far.
+    pub fn set_exploration(&mut self, exploration: f64) {
+        self.exploration = exploration;
+    }
+
+    pub fn playout(&self) -> &Playout<E> {
+        &self.playout
+    }
+
+    pub fn limits(&self) -> &SearchLimits {
+        &self.limits
+    }
+
+    pub fn set_limits(&mut self, limits: SearchLimits) {
+        self.limits = limits;
+    }
+
+    pub fn set_seed(&mut self, seed: u64) {
+        self.rng = Rng::new(seed);
+    }
+
+    /// Number of nodes in the tree kept from the last search.
+    pub fn tree_size(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Throws away the tree, so that the next search starts from scratch.
+    pub fn clear(&mut self) {
+        self.nodes.clear();
+    }
+
+    /// Searches until the playouts, the time budget or the stop flag run out, and returns the
+    /// most visited move. `score` is the expected result of that move for `player`, from
+    /// `-WIN_SCORE` for a sure loss to `WIN_SCORE` for a sure win, `pv` follows the most
+    /// visited replies, and `nodes` counts the playouts of this search.
+    pub fn search(&mut self, board: &Board, player: Player) -> SearchResult {
+        let deadline = self
+            .limits
+            .time_budget
+            .map(|budget| Instant::now() + budget);
+        if !self.reuse(board, player) {
+            self.nodes.clear();
+            self.nodes
+                .push(Node::new(board.clone(), player, None, None));
+        }
+
+        let limits = &self.limits;
+        let max_nodes = match limits.max_nodes {
+            None if limits.time_budget.is_none() && limits.stop_flag.is_none() => {
+                Some(DEFAULT_PLAYOUTS)
+            }
+            max_nodes => max_nodes,
+        };
+        let mut playouts = 0;
+        loop {
+            if let Some(max_nodes) = max_nodes {
+                if playouts >= max_nodes {
+                    break;
+                }
+            }
+            if let Some(deadline) = deadline {
+                if Instant::now() >= deadline {
+                    break;
+                }
+            }
+            if let Some(flag) = &self.limits.stop_flag {
+                if flag.load(AtomicOrdering::Relaxed) {
+                    break;
+                }
+            }
+            let leaf = self.expand(self.select());
+            let winner = self.play_out(self.nodes[leaf].board.clone(), self.nodes[leaf].to_move);
+            self.backpropagate(leaf, winner);
+            playouts += 1;
+        }
+
+        let mut pv = vec![];
+        let mut node = 0;
+        while let Some(child) = self.most_visited(node) {
+            pv.extend(self.nodes[child].ply);
+            node = child;
+        }
+        let (best_move, score) = match self.most_visited(0) {
+            Some(child) => {
+                let child = &self.nodes[child];
+                let best_move = match child.ply {
+                    Some(Ply::Move(_, pos)) => Some(pos),
+                    _ => None,
+                };
+                let expected = child.reward / child.visits as f64;
+                (
+                    best_move,
+                    ((2.0 * expected - 1.0) * WIN_SCORE as f64) as i32,
+                )
+            }
+            // Not a single playout: fall back to any legal move
+            None => (board.all_legal_moves(player).next(), 0),
+        };
+        SearchResult {
+            best_move,
+            score,
+            depth: pv.len() as u32,
+            pv,
+            nodes: playouts,
+        }
+    }
+
+    /// Looks for the position a few plies below the root of the kept tree, and if found
+    /// makes its subtree the new tree.
+    fn reuse(&mut self, board: &Board, player: Player) -> bool {
+        const MAX_PLIES: u32 = 4;
+        if self.nodes.is_empty() {
+            return false;
+        }
+        let mut queue = VecDeque::new();
+        queue.push_back((0, 0));
+        let mut found = None;
+        while let Some((i, plies)) = queue.pop_front() {
+            let node = &self.nodes[i];
+            if node.to_move == player && node.board == *board {
+                found = Some(i);
+                break;
+            }
+            if plies < MAX_PLIES {
+                queue.extend(node.children.iter().map(|&child| (child, plies + 1)));
+            }
+        }
+        let root = match found {
+            Some(root) => root,
+            None => return false,
+        };
+
+        let mut old = std::mem::take(&mut self.nodes);
+        let mut root_node = old[root].clone();
+        root_node.ply = None;
+        root_node.parent = None;
+        self.nodes.push(root_node);
+        let mut stack = vec![0];
+        while let Some(new) = stack.pop() {
+            let children = std::mem::take(&mut self.nodes[new].children);
+            for child in children {
+                let mut node = std::mem::replace(
+                    &mut old[child],
+                    Node::new(Board::empty(), Player::Player1, None, None),
+                );
+                node.parent = Some(new);
+                let idx = self.nodes.len();
+                self.nodes[new].children.push(idx);
+                stack.push(idx);
+                self.nodes.push(node);
+            }
+        }
+        true
+    }
+
+    /// Walks down from the root along the best UCT values to a node that is not fully
+    /// expanded or ends the game.
+    fn select(&self) -> usize {
+        let mut node = 0;
+        while self.nodes[node].is_expanded() && !self.nodes[node].children.is_empty() {
+            let parent = &self.nodes[node];
+            let log_visits = (parent.visits as f64).ln();
+            node = *parent
+                .children
+                .iter()
+                .max_by(|&&a, &&b| {
+                    let a = self.uct(a, log_visits);
+                    let b = self.uct(b, log_visits);
+                    a.partial_cmp(&b).unwrap()
+                })
+                .unwrap();
+        }
+        node
+    }
+
+    fn uct(&self, node: usize, log_parent_visits: f64) -> f64 {
+        let node = &self.nodes[node];
+        let visits = node.visits as f64;
+        node.reward / visits + self.exploration * (log_parent_visits / visits).sqrt()
+    }
+
+    /// Adds a child for one of the untried plies of `node`, or returns `node` itself if the
+    /// game is over there.
+    fn expand(&mut self, node: usize) -> usize {
+        let parent = &mut self.nodes[node];
+        let player = parent.to_move;
+        let mut board = parent.board.clone();
+        let ply = if parent.untried != 0 {
+            let idx = self.rng.pick_bit(parent.untried);
+            parent.untried &= !(1 << idx);
+            let pos = Position { idx: idx as u8 };
+            board.place_piece(pos, player);
+            Ply::Move(player, pos)
+        } else if parent.untried_pass {
+            parent.untried_pass = false;
+            Ply::Pass(player)
+        } else {
+            return node;
+        };
+        let child = self.nodes.len();
+        self.nodes[node].children.push(child);
+        self.nodes
+            .push(Node::new(board, player.opponent(), Some(ply), Some(node)));
+        child
+    }
+
+    /// Plays the game out to the end and returns the winner.
+    fn play_out(&mut self, mut board: Board, mut player: Player) -> Option<Player> {
+        loop {
+            let moves = board.legal_moves_mask(player);
+            if moves == 0 {
+                if board.legal_moves_mask(player.opponent()) == 0 {
+                    break;
+                }
+                player = player.opponent();
+                continue;
+            }
+            let pos = self.pick_move(&board, player, moves);
+            board.place_piece(pos, player);
+            player = player.opponent();
+        }
+        let (p1, p2) = (board.player1_count(), board.player2_count());
+        if p1 > p2 {
+            Some(Player::Player1)
+        } else if p2 > p1 {
+            Some(Player::Player2)
+        } else {
+            None
+        }
+    }
+
+    fn pick_move(&mut self, board: &Board, player: Player, moves: u64) -> Position {
+        let evaluator = match &self.playout {
+            Playout::Random => {
+                let idx = self.rng.pick_bit(moves);
+                return Position { idx: idx as u8 };
+            }
+            Playout::Guided(evaluator) => evaluator,
+        };
+        let mut best = i32::MIN;
+        let mut best_moves = 0;
+        for idx in Bits(moves) {
+            let pos = Position { idx };
+            let mut child = board.clone();
+            child.place_piece(pos, player);
+            let score = evaluator.evaluate(&child, player);
+            if score > best {
+                best = score;
+                best_moves = 0;
+            }
+            if score == best {
+                best_moves |= 1 << pos.idx;
+            }
+        }
+        let idx = self.rng.pick_bit(best_moves);
+        Position { idx: idx as u8 }
+    }
+
+    fn backpropagate(&mut self, mut node: usize, winner: Option<Player>) {
+        loop {
+            let n = &mut self.nodes[node];
+            n.visits += 1;
+            n.reward += match winner {
+                Some(winner) if winner == n.mover() => 1.0,
+                Some(_) => 0.0,
+                None => 0.5,
+            };
+            match n.parent {
+                Some(parent) => node = parent,
+                None => break,
+            }
+        }
+    }
+
+    fn most_visited(&self, node: usize) -> Option<usize> {
+        self.nodes[node]
+            .children
+            .iter()
+            .copied()
+            .max_by_key(|&child| self.nodes[child].visits)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::Disc;
+    use crate::endgame::{self, Outcome};
+    use crate::rng::random_position;
+    use std::sync::atomic::AtomicBool;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    /// A random position with `empties` empty squares where the side to move has a move
+    fn playable_position(rng: &mut Rng, empties: usize) -> (Board, Player) {
+        loop {
+            if let Some((board, player)) = random_position(rng, empties) {
+                if board.legal_moves_mask(player) != 0 {
+                    return (board, player);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_finds_winning_moves() {
+        let mut rng = Rng::new(0x9e37_79b9_7f4a_7c15);
+        let mut tested = 0;
+        for _ in 0..10 {
+            let (board, player) = playable_position(&mut rng, 7);
+            let solution = endgame::solve_wld(&board, player);
+            if solution.outcome() != Outcome::Win {
+                continue;
+            }
+            let mut searcher = MctsSearcher::new(5_000);
+            let pos = searcher.search(&board, player).best_move.unwrap();
+            let mut child = board.clone();
+            child.place_piece(pos, player);
+            let reply = endgame::solve_wld(&child, player.opponent());
+            // The opponent may have to pass, in which case it is still our move
+            let won = if child.legal_moves_mask(player.opponent()) == 0 {
+                endgame::solve_wld(&child, player).outcome() == Outcome::Win
+            } else {
+                reply.outcome() == Outcome::Loss
+            };
+            assert!(won, "{:?} throws away the win", pos);
+            tested += 1;
+        }
+        assert!(tested > 0);
+    }
+
+    #[test]
+    fn test_guided_playouts() {
+        let mut searcher =
+            MctsSearcher::with_playout(500, Playout::Guided(SquareWeights::default()));
+        let board = Board::default();
+        let result = searcher.search(&board, Player::Player1);
+        assert!(board.is_legal_move(result.best_move.unwrap(), Player::Player1));
+        assert_eq!(result.nodes, 500);
+        assert_eq!(
+            result.pv.first(),
+            Some(&Ply::Move(Player::Player1, result.best_move.unwrap()))
+        );
+        assert!(result.score.abs() <= WIN_SCORE);
+    }
+
+    #[test]
+    fn test_exploration() {
+        // Without exploration the first move that wins a playout takes every later one
+        let board = Board::default();
+        let mut greedy = MctsSearcher::new(400);
+        greedy.set_exploration(0.0);
+        greedy.search(&board, Player::Player1);
+        let mut explorer = MctsSearcher::new(400);
+        explorer.set_exploration(10.0);
+        explorer.search(&board, Player::Player1);
+        let widest = |searcher: &MctsSearcher| {
+            searcher.nodes[0]
+                .children
+                .iter()
+                .map(|&child| searcher.nodes[child].visits)
+                .max()
+                .unwrap()
+        };
+        assert!(widest(&greedy) > widest(&explorer));
+    }
+
+    #[test]
+    fn test_tree_reuse() {
+        let mut searcher = MctsSearcher::new(2_000);
+        let mut board = Board::default();
+        let result = searcher.search(&board, Player::Player1);
+        let size = searcher.tree_size();
+        assert!(size > 1);
+        for ply in &result.pv[..2] {
+            if let Ply::Move(player, pos) = *ply {
+                board.place_piece(pos, player);
+            }
+        }
+        let result = searcher.search(&board, Player::Player1);
+        assert!(board.is_legal_move(result.best_move.unwrap(), Player::Player1));
+        // A fresh tree would have at most one node per playout besides the root
+        assert!(searcher.tree_size() > result.nodes as usize + 1);
+
+        // A position that is not in the tree starts over
+        searcher.set_limits(SearchLimits {
+            max_nodes: Some(10),
+            ..SearchLimits::default()
+        });
+        searcher.search(&Board::default(), Player::Player2);
+        assert!(searcher.tree_size() <= 11);
+    }
+
+    #[test]
+    fn test_passes() {
+        // Player 2 has no move on this board, player 1 does
+        let mut board = Board::empty();
+        board.set_piece(Position::at(0, 0), Disc::Player1);
+        board.set_piece(Position::at(0, 1), Disc::Player2);
+        board.set_piece(Position::at(7, 7), Disc::Player1);
+        let mut searcher = MctsSearcher::new(100);
+        let result = searcher.search(&board, Player::Player2);
+        assert_eq!(result.best_move, None);
+        assert_eq!(
+            &result.pv[..2],
+            &[
+                Ply::Pass(Player::Player2),
+                Ply::Move(Player::Player1, Position::at(0, 2))
+            ]
+        );
+        assert_eq!(result.score, -WIN_SCORE);
+    }
+
+    #[test]
+    fn test_game_over() {
+        let mut board = Board::empty();
+        board.set_piece(Position::at(0, 0), Disc::Player1);
+        let mut searcher = MctsSearcher::new(10);
+        let result = searcher.search(&board, Player::Player1);
+        assert_eq!(result.best_move, None);
+        assert!(result.pv.is_empty());
+    }
+
+    #[test]
+    fn test_limits() {
+        let board = Board::default();
+        let mut searcher = MctsSearcher::new(0);
+        searcher.set_limits(SearchLimits::time(Duration::from_millis(20)));
+        let start = Instant::now();
+        let result = searcher.search(&board, Player::Player1);
+        assert!(start.elapsed() < Duration::from_secs(1));
+        assert!(result.nodes > 0);
+
+        let flag = Arc::new(AtomicBool::new(true));
+        searcher.set_limits(SearchLimits {
+            stop_flag: Some(flag),
+            ..SearchLimits::default()
+        });
+        searcher.clear();
+        let result = searcher.search(&board, Player::Player1);
+        assert_eq!(result.nodes, 0);
+        // Without a single playout any legal move is still returned
+        assert!(board.is_legal_move(result.best_move.unwrap(), Player::Player1));
+    }
+
+    #[test]
+    fn test_depth_limit_falls_back_to_default_playouts() {
+        let mut searcher = MctsSearcher::new(0);
+        searcher.set_limits(SearchLimits::depth(3));
+        let result = searcher.search(&Board::default(), Player::Player1);
+        assert_eq!(result.nodes, DEFAULT_PLAYOUTS);
+    }
+}
@@ -0,0 +1,169 @@
+//! Transposition table caching search results by Zobrist key.
+
+use crate::position::Position;
+use std::fmt;
+
+/// How the stored score relates to the true score of the position.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Bound {
+    Exact,
+    /// The true score is at least the stored score
+    Lower,
+    /// The true score is at most the stored score
+    Upper,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Entry {
+    pub key: u64,
+    pub depth: u32,
+    pub bound: Bound,
+    pub score: i32,
+    pub best_move: Option<Position>,
+    generation: u8,
+}
+
+impl Entry {
+    pub fn new(
+        key: u64,
+        depth: u32,
+        bound: Bound,
+        score: i32,
+        best_move: Option<Position>,
+    ) -> Self {
+        Self {
+            key,
+            depth,
+            bound,
+            score,
+            best_move,
+            generation: 0,
+        }
+    }
+}
+
+/// A fixed-size, direct-mapped table.
+///
+/// A new entry replaces the one in its slot if the slot holds the same position, an entry
+/// from an earlier search, or an entry searched no deeper than the new one.
+#[derive(Clone)]
+pub struct TranspositionTable {
+    slots: Vec<Option<Entry>>,
+    generation: u8,
+}
+
+impl fmt::Debug for TranspositionTable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TranspositionTable")
+            .field("capacity", &self.capacity())
+            .field("generation", &self.generation)
+            .finish()
+    }
+}
+
+impl TranspositionTable {
+    /// Creates a table with room for `capacity` entries, rounded down to a power of two.
+    pub fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        let capacity = 1 << (usize::BITS - 1 - capacity.leading_zeros());
+        Self {
+            slots: vec![None; capacity],
+            generation: 0,
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// Number of occupied slots.
+    pub fn len(&self) -> usize {
+        self.slots.iter().filter(|slot| slot.is_some()).count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.slots.iter().all(Option::is_none)
+    }
+
+    pub fn clear(&mut self) {
+        self.slots.iter_mut().for_each(|slot| *slot = None);
+        self.generation = 0;
+    }
+
+    /// Marks every stored entry as belonging to an earlier search, so it gets replaced first.
+    pub fn new_search(&mut self) {
+        self.generation = self.generation.wrapping_add(1);
+    }
+
+    fn slot(&self, key: u64) -> usize {
+        key as usize & (self.slots.len() - 1)
+    }
+
+    pub fn probe(&self, key: u64) -> Option<Entry> {
+        self.slots[self.slot(key)].filter(|entry| entry.key == key)
+    }
+
+    pub fn store(&mut self, mut entry: Entry) {
+        entry.generation = self.generation;
+        let slot = self.slot(entry.key);
+        let replace = match self.slots[slot] {
+            None => true,
+            Some(old) => {
+                old.key == entry.key
+                    || old.generation != self.generation
+                    || old.depth <= entry.depth
+            }
+        };
+        if replace {
+            self.slots[slot] = Some(entry);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_capacity_is_power_of_two() {
+        assert_eq!(TranspositionTable::new(1000).capacity(), 512);
+        assert_eq!(TranspositionTable::new(1024).capacity(), 1024);
+        assert_eq!(TranspositionTable::new(0).capacity(), 1);
+    }
+
+    #[test]
+    fn test_store_and_probe() {
+        let mut tt = TranspositionTable::new(16);
+        assert!(tt.is_empty());
+        let entry = Entry::new(42, 3, Bound::Exact, 7, Some(Position::at(2, 3)));
+        tt.store(entry);
+        assert_eq!(tt.probe(42), Some(entry));
+        assert_eq!(tt.probe(42 + 16), None);
+        assert_eq!(tt.len(), 1);
+        tt.clear();
+        assert_eq!(tt.probe(42), None);
+    }
+
+    #[test]
+    fn test_replacement_policy() {
+        let mut tt = TranspositionTable::new(16);
+        let deep = Entry::new(1, 6, Bound::Exact, 10, None);
+        let shallow = Entry::new(17, 2, Bound::Lower, 3, None);
+        tt.store(deep);
+        tt.store(shallow);
+        assert_eq!(tt.probe(1), Some(deep));
+        assert_eq!(tt.probe(17), None);
+
+        // The same position is always refreshed
+        let refreshed = Entry::new(1, 1, Bound::Upper, 0, None);
+        tt.store(refreshed);
+        assert_eq!(tt.probe(1), Some(refreshed));
+
+        // Entries from an earlier search give way to anything
+        tt.store(deep);
+        tt.new_search();
+        tt.store(shallow);
+        assert_eq!(tt.probe(1), None);
+        assert_eq!(tt.probe(17).map(|e| e.score), Some(3));
+    }
+}
@@ -1,31 +1,58 @@
+use crate::error::MoveError;
 use crate::position::{Position, MAX_VALID_POS};
+use std::convert::TryInto;
+use std::hash::{Hash, Hasher};
 use std::ops::Index;
 
+pub(crate) mod bitboard;
+mod delta;
 mod discs;
+mod features;
 mod neighbours;
+mod rules;
+mod sized;
 mod strider;
+mod symmetry;
+mod text;
+mod zobrist;
 
-use discs::{Disc, Player};
-use neighbours::Neighbours;
-use strider::{Direction, Strider};
+use bitboard::Bits;
+
+pub use delta::Move;
+pub use discs::{Disc, Player};
+pub use features::{EmptyRegion, PositionFeatures};
+pub use neighbours::Neighbours;
+pub use rules::Rules;
+pub use sized::{Board10, Board6, SizedBoard, SizedMove, SizedPosition, SizedStrider};
+pub use strider::{Direction, Strider};
+pub use symmetry::Symmetry;
+pub use text::ParseBoardError;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Board {
-    p1: [u8; 8],
-    p2: [u8; 8],
+    /// Bitboards of each player's discs, with bit `row * 8 + col` standing for `(row, col)`
+    p1: u64,
+    p2: u64,
+    /// Bitboard of the blocked squares, which hold no disc and break lines
+    blocked: u64,
+    /// Zobrist hash of the discs and blocked squares, kept up to date by every change
+    hash: u64,
+}
+
+impl Hash for Board {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_u64(self.hash);
+    }
 }
 
-impl<T: Into<Position>> Index<T> for Board {
+impl Index<Position> for Board {
     type Output = Disc;
-    fn index(&self, index: T) -> &Self::Output {
-        let index = index.into();
-        if index.idx > MAX_VALID_POS {
-            panic!("Index out of bounds")
-        }
-        let row = (index.idx >> 3) & 0b111;
-        let col = index.idx & 0b111;
-        let p1 = (self.p1[row as usize] >> col) & 1;
-        let p2 = (self.p2[row as usize] >> col) & 1;
+    fn index(&self, index: Position) -> &Self::Output {
+        if self.blocked >> index.idx & 1 == 1 {
+            return &Disc::Blocked;
+        }
+        let p1 = (self.p1 >> index.idx) & 1;
+        let p2 = (self.p2 >> index.idx) & 1;
         match (p1, p2) {
             (0, 0) => &Disc::Empty,
             (0, 1) => &Disc::Player2,
@@ -37,30 +64,24 @@ impl<T: Into<Position>> Index<T> for Board {
 
 impl Default for Board {
     fn default() -> Self {
-        let mut board = Self {
-            p1: [0; 8],
-            p2: [0; 8],
-        };
-        board.p1[3] = 0b00010000;
-        board.p1[4] = 0b00001000;
-        board.p2[3] = 0b00001000;
-        board.p2[4] = 0b00010000;
-        board
+        Self::from_bitboards(1 << 28 | 1 << 35, 1 << 27 | 1 << 36)
     }
 }
 
 impl From<[[Disc; 8]; 8]> for Board {
     fn from(b: [[Disc; 8]; 8]) -> Self {
         let mut board = Board::empty();
-        for i in 0..8 {
-            for j in 0..8 {
-                match b[i][j] {
-                    Disc::Player1 => board.p1[i] |= 1 << j,
-                    Disc::Player2 => board.p2[i] |= 1 << j,
+        for (i, row) in b.iter().enumerate() {
+            for (j, &disc) in row.iter().enumerate() {
+                match disc {
+                    Disc::Player1 => board.p1 |= 1 << (i * 8 + j),
+                    Disc::Player2 => board.p2 |= 1 << (i * 8 + j),
+                    Disc::Blocked => board.blocked |= 1 << (i * 8 + j),
                     Disc::Empty => (),
                 }
             }
         }
+        board.hash = zobrist::hash(board.p1, board.p2, board.blocked);
         board
     }
 }
@@ -68,15 +89,15 @@ impl From<[[Disc; 8]; 8]> for Board {
 impl From<[Disc; 64]> for Board {
     fn from(b: [Disc; 64]) -> Self {
         let mut board = Board::empty();
-        for i in 0..8 {
-            for j in 0..8 {
-                match b[i * 8 + j] {
-                    Disc::Player1 => board.p1[i] |= 1 << j,
-                    Disc::Player2 => board.p2[i] |= 1 << j,
-                    Disc::Empty => (),
-                }
+        for (i, &disc) in b.iter().enumerate() {
+            match disc {
+                Disc::Player1 => board.p1 |= 1 << i,
+                Disc::Player2 => board.p2 |= 1 << i,
+                Disc::Blocked => board.blocked |= 1 << i,
+                Disc::Empty => (),
             }
         }
+        board.hash = zobrist::hash(board.p1, board.p2, board.blocked);
         board
     }
 }
@@ -84,52 +105,170 @@ impl From<[Disc; 64]> for Board {
 impl Board {
     pub const fn empty() -> Self {
         Self {
-            p1: [0; 8],
-            p2: [0; 8],
+            p1: 0,
+            p2: 0,
+            blocked: 0,
+            hash: 0,
         }
     }
 
-    pub fn iter(&self) -> Iter {
+    fn from_bitboards(p1: u64, p2: u64) -> Self {
+        Self {
+            p1,
+            p2,
+            blocked: 0,
+            hash: zobrist::hash(p1, p2, 0),
+        }
+    }
+
+    pub fn iter(&self) -> Iter<'_> {
         Iter {
             board: self,
-            cur_pos: (0u8, 0u8).into(),
+            cur_pos: Position { idx: 0 },
         }
     }
 
-    pub fn get_piece<T: Into<Position>>(&self, pos: T) -> Disc {
-        self[pos.into()]
+    pub fn get_piece(&self, pos: Position) -> Disc {
+        self[pos]
     }
 
-    pub fn set_piece<T: Into<Position>>(&mut self, pos: T, val: Disc) {
-        let pos = pos.into();
-        let row = pos.row();
-        let col = pos.col();
+    /// Returns the disc at `pos`, or `None` if `pos` does not describe a square on the board.
+    pub fn get<T: TryInto<Position>>(&self, pos: T) -> Option<Disc> {
+        pos.try_into().ok().map(|pos| self[pos])
+    }
+
+    pub fn set_piece(&mut self, pos: Position, val: Disc) {
+        let bit = 1 << pos.idx;
+        let idx = pos.index();
+        if self.p1 & bit != 0 {
+            self.hash ^= zobrist::KEYS[0][idx];
+        }
+        if self.p2 & bit != 0 {
+            self.hash ^= zobrist::KEYS[1][idx];
+        }
+        if self.blocked & bit != 0 {
+            self.hash ^= zobrist::KEYS[2][idx];
+        }
+        self.blocked &= !bit;
         match val {
             Disc::Empty => {
-                self.p1[row] &= !(1 << col);
-                self.p2[row] &= !(1 << col);
+                self.p1 &= !bit;
+                self.p2 &= !bit;
+            }
+            Disc::Blocked => {
+                self.p1 &= !bit;
+                self.p2 &= !bit;
+                self.blocked |= bit;
+                self.hash ^= zobrist::KEYS[2][idx];
             }
             Disc::Player1 => {
-                self.p1[row] |= 1 << col;
-                self.p2[row] &= !(1 << col);
+                self.p1 |= bit;
+                self.p2 &= !bit;
+                self.hash ^= zobrist::KEYS[0][idx];
             }
             Disc::Player2 => {
-                self.p1[row] &= !(1 << col);
-                self.p2[row] |= 1 << col;
+                self.p1 &= !bit;
+                self.p2 |= bit;
+                self.hash ^= zobrist::KEYS[1][idx];
             }
         }
     }
 
+    /// The board rotated or reflected by `sym`.
+    pub fn transform(&self, sym: Symmetry) -> Board {
+        let p1 = sym.apply_bitboard(self.p1);
+        let p2 = sym.apply_bitboard(self.p2);
+        let blocked = sym.apply_bitboard(self.blocked);
+        Board {
+            p1,
+            p2,
+            blocked,
+            hash: zobrist::hash(p1, p2, blocked),
+        }
+    }
+
+    /// The representative of this board's symmetry class, and the symmetry taking this board to
+    /// it. Equivalent boards share the same representative; a move on the representative maps
+    /// back to this board with the inverse of the symmetry.
+    pub fn canonical(&self) -> (Board, Symmetry) {
+        Symmetry::ALL
+            .iter()
+            .map(|&sym| (self.transform(sym), sym))
+            .min_by_key(|(board, _)| (board.p1, board.p2, board.blocked))
+            .unwrap()
+    }
+
+    /// Zobrist key of the position with `to_move` to play. Unlike the [`Hash`] impl, which
+    /// only covers the squares, this tells apart the same board with different sides to move.
+    pub fn zobrist_key(&self, to_move: Player) -> u64 {
+        match to_move {
+            Player::Player1 => self.hash,
+            Player::Player2 => self.hash ^ zobrist::SIDE,
+        }
+    }
+
     pub fn player1_count(&self) -> usize {
-        self.p1.iter().fold(0, |acc, &row| acc + row.count_ones()) as usize
+        self.p1.count_ones() as usize
     }
 
     pub fn player2_count(&self) -> usize {
-        self.p2.iter().fold(0, |acc, &row| acc + row.count_ones()) as usize
+        self.p2.count_ones() as usize
+    }
+
+    /// Bitboard of the discs owned by `player`.
+    pub fn discs(&self, player: Player) -> u64 {
+        match player {
+            Player::Player1 => self.p1,
+            Player::Player2 => self.p2,
+        }
+    }
+
+    /// Bitboard of the empty squares, leaving out blocked ones.
+    pub fn empty_squares(&self) -> u64 {
+        !(self.p1 | self.p2 | self.blocked)
+    }
+
+    /// Bitboard of the blocked squares.
+    pub fn blocked_squares(&self) -> u64 {
+        self.blocked
+    }
+
+    /// Blocks the squares of `mask` and unblocks all others. Discs on newly blocked squares
+    /// are removed.
+    pub fn set_blocked(&mut self, mask: u64) {
+        for idx in Bits((self.p1 | self.p2) & mask) {
+            self.set_piece(Position { idx }, Disc::Empty);
+        }
+        self.hash ^= zobrist::blocked_hash(self.blocked ^ mask);
+        self.blocked = mask;
+    }
+
+    /// Bitboard of every square `player` can legally move to.
+    pub fn legal_moves_mask(&self, player: Player) -> u64 {
+        bitboard::moves(self.discs(player), self.discs(player.opponent())) & !self.blocked
     }
 
-    pub fn neighbours<T: Into<Position>>(&self, pos: T) -> Neighbours {
-        let pos = pos.into();
+    /// Bitboard of the discs of `player` that can never be flipped, whatever is played. This
+    /// is a safe underestimate: some stable discs may be missing, but none is wrongly
+    /// included.
+    pub fn stable_discs(&self, player: Player) -> u64 {
+        bitboard::stable(
+            self.discs(player),
+            self.discs(player.opponent()),
+            self.blocked,
+        )
+    }
+
+    /// Bitboard of the discs that a move by `player` on `pos` would flip. This is `0` if the
+    /// square is occupied or the move is illegal.
+    pub fn flips(&self, pos: Position, player: Player) -> u64 {
+        if self.empty_squares() >> pos.idx & 1 == 0 {
+            return 0;
+        }
+        bitboard::flips(pos.idx, self.discs(player), self.discs(player.opponent()))
+    }
+
+    pub fn neighbours(&self, pos: Position) -> Neighbours<'_> {
         let row = pos.row();
         let col = pos.col();
         match (row, col) {
@@ -145,13 +284,7 @@ impl Board {
         }
     }
 
-    pub fn get_points_in_line<T, U>(&self, pos: T, neighbour_pos: U) -> Strider
-    where
-        T: Into<Position>,
-        U: Into<Position>,
-    {
-        let pos = pos.into();
-        let neighbour_pos = neighbour_pos.into();
+    pub fn get_points_in_line(&self, pos: Position, neighbour_pos: Position) -> Strider<'_> {
         let dir = match pos.idx as i8 - neighbour_pos.idx as i8 {
             8 => Direction::Up,
             7 => Direction::UpRight,
@@ -170,70 +303,96 @@ impl Board {
         }
     }
 
-    pub fn is_legal_move<T: Into<Position>>(&self, pos: T, player: Player) -> bool {
-        let pos = pos.into();
-        if self[pos] != Disc::Empty {
-            return false;
-        }
-        let opponent = player.opponent();
-        let player = player;
-        for (neighbour_pos, neighbour_disc) in self.neighbours(pos) {
-            if neighbour_disc == opponent {
-                for (_, disc) in self.get_points_in_line(pos, neighbour_pos) {
-                    match disc {
-                        // All cases are covered as opponent and player are different player discs
-                        Disc::Empty => break,
-                        x if x == opponent => continue,
-                        x if x == player => return true,
-                        _ => unreachable!(),
-                    }
-                }
+    pub fn is_legal_move(&self, pos: Position, player: Player) -> bool {
+        self.legal_moves_mask(player) >> pos.idx & 1 == 1
+    }
+
+    pub fn all_legal_moves(&self, player: Player) -> impl Iterator<Item = Position> {
+        Bits(self.legal_moves_mask(player)).map(|idx| Position { idx })
+    }
+
+    /// Places a disc for `player` and flips the discs it captures, without checking that the
+    /// move is legal. Use [`Board::try_place_piece`] for untrusted input.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `pos` is blocked.
+    pub fn place_piece(&mut self, pos: Position, player: Player) -> Move {
+        assert!(
+            self.blocked >> pos.idx & 1 == 0,
+            "cannot place a disc on a blocked square"
+        );
+        let flips = self.flips(pos, player);
+        self.apply(pos, player, flips);
+        Move::new(pos, player, flips)
+    }
+
+    /// Like [`Board::place_piece`], but leaves the board untouched and returns an error if the
+    /// move is not legal for `player`.
+    pub fn try_place_piece(&mut self, pos: Position, player: Player) -> Result<Move, MoveError> {
+        match self[pos] {
+            Disc::Empty => (),
+            Disc::Blocked => return Err(MoveError::Blocked),
+            _ => return Err(MoveError::Occupied),
+        }
+        let flips = self.flips(pos, player);
+        if flips == 0 {
+            return Err(MoveError::NoFlips);
+        }
+        self.apply(pos, player, flips);
+        Ok(Move::new(pos, player, flips))
+    }
+
+    /// Takes back `mv`, which must be the last move made on this board.
+    pub fn unmake(&mut self, mv: &Move) {
+        let placed = 1 << mv.pos().idx;
+        let flips = mv.flips();
+        debug_assert!(self.discs(mv.player()) & (placed | flips) == placed | flips);
+        self.update_hash(mv.pos(), mv.player(), flips);
+        match mv.player() {
+            Player::Player1 => {
+                self.p1 &= !(placed | flips);
+                self.p2 |= flips;
+            }
+            Player::Player2 => {
+                self.p2 &= !(placed | flips);
+                self.p1 |= flips;
             }
         }
-        false
     }
 
-    pub fn all_legal_moves<'a>(&'a self, player: Player) -> impl Iterator<Item = Position> + 'a {
-        (0..8u8)
-            .flat_map(|i| (0..8u8).map(move |j| (i, j)))
-            .filter(move |&pos| self.is_legal_move(pos, player))
-            .map(|p| p.into())
+    /// The move [`Board::place_piece`] would make, without making it. Its flips are empty if
+    /// the move is illegal.
+    pub fn place_piece_dry_run(&self, pos: Position, player: Player) -> Move {
+        Move::new(pos, player, self.flips(pos, player))
     }
 
-    pub fn place_piece<T: Into<Position>>(&mut self, pos: T, player: Player) -> Vec<Position> {
-        let pos = pos.into();
-        let turned_pieces = self.place_piece_dry_run(pos, player);
-        self.set_piece(pos, player.clone().into());
-        turned_pieces
-            .iter()
-            .for_each(|&pos| self.set_piece(pos, player.into()));
-        turned_pieces
-    }
-
-    pub fn place_piece_dry_run<T: Into<Position>>(&self, pos: T, player: Player) -> Vec<Position> {
-        let pos = pos.into();
-        let mut turned_pieces = vec![];
-        let opponent = player.opponent();
-        for (neighbour_pos, neighbour_piece) in self.neighbours(pos) {
-            if neighbour_piece == opponent {
-                for (_, disc) in self.get_points_in_line(pos, neighbour_pos) {
-                    match disc {
-                        Disc::Empty => break,
-                        x if x == opponent => continue,
-                        x if x == player => {
-                            turned_pieces.extend(
-                                self.get_points_in_line(pos, neighbour_pos)
-                                    .take_while(|&(_, d)| d != player)
-                                    .map(|(p, _)| p),
-                            );
-                            break;
-                        }
-                        _ => unreachable!(),
-                    }
-                }
+    fn apply(&mut self, pos: Position, player: Player, flips: u64) {
+        let placed = 1 << pos.idx;
+        self.update_hash(pos, player, flips);
+        match player {
+            Player::Player1 => {
+                self.p1 |= placed | flips;
+                self.p2 &= !(placed | flips);
+            }
+            Player::Player2 => {
+                self.p2 |= placed | flips;
+                self.p1 &= !(placed | flips);
             }
         }
-        turned_pieces
+    }
+
+    /// Toggles the disc `player` places on `pos` and the discs it flips in the hash, which
+    /// both makes and takes back the move.
+    fn update_hash(&mut self, pos: Position, player: Player, flips: u64) {
+        let (own_keys, opponent_keys) = match player {
+            Player::Player1 => (&zobrist::KEYS[0], &zobrist::KEYS[1]),
+            Player::Player2 => (&zobrist::KEYS[1], &zobrist::KEYS[0]),
+        };
+        self.hash ^= own_keys[pos.index()];
+        for idx in Bits(flips) {
+            self.hash ^= own_keys[idx as usize] ^ opponent_keys[idx as usize];
+        }
     }
 }
 
@@ -262,23 +421,24 @@ impl<'a> Iterator for Iter<'a> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::rng::{RandomGame, Rng};
 
     #[test]
     fn test_default_impl() {
         let board = Board::default();
-        assert_eq!(board.p1, [0, 0, 0, 16, 8, 0, 0, 0]);
-        assert_eq!(board.p2, [0, 0, 0, 8, 16, 0, 0, 0]);
+        assert_eq!(board.p1, 0x0000_0008_1000_0000);
+        assert_eq!(board.p2, 0x0000_0010_0800_0000);
     }
 
     #[test]
     fn test_indexing() {
         let board = Board::default();
-        assert_eq!(board[(0u8, 5u8)], Disc::Empty);
-        assert_eq!(board[(3u8, 3u8)], Disc::Player2);
-        assert_eq!(board[(3u8, 4u8)], Disc::Player1);
-        assert_eq!(board[(4u8, 3u8)], Disc::Player1);
-        assert_eq!(board[(4u8, 4u8)], Disc::Player2);
-        assert_eq!(board[(7u8, 7u8)], Disc::Empty);
+        assert_eq!(board[Position::at(0, 5)], Disc::Empty);
+        assert_eq!(board[Position::at(3, 3)], Disc::Player2);
+        assert_eq!(board[Position::at(3, 4)], Disc::Player1);
+        assert_eq!(board[Position::at(4, 3)], Disc::Player1);
+        assert_eq!(board[Position::at(4, 4)], Disc::Player2);
+        assert_eq!(board[Position::at(7, 7)], Disc::Empty);
     }
 
     #[test]
@@ -336,56 +496,69 @@ mod tests {
     #[test]
     fn test_value_getter_setter() {
         let mut board = Board::default();
-        assert_eq!(board[(0u8, 3u8)], Disc::Empty);
-        assert_eq!(board.get_piece((0u8, 3u8)), Disc::Empty);
-        board.set_piece((0u8, 3u8), Disc::Player1);
-        assert_eq!(board[(0u8, 3u8)], Disc::Player1);
-        assert_eq!(board.get_piece((0u8, 3u8)), Disc::Player1);
-        assert_eq!(board[(3u8, 3u8)], Disc::Player2);
-        assert_eq!(board.get_piece((3u8, 3u8)), Disc::Player2);
-        board.set_piece((3u8, 3u8), Disc::Player1);
-        assert_eq!(board[(3u8, 3u8)], Disc::Player1);
-        assert_eq!(board.get_piece((3u8, 3u8)), Disc::Player1);
+        assert_eq!(board[Position::at(0, 3)], Disc::Empty);
+        assert_eq!(board.get_piece(Position::at(0, 3)), Disc::Empty);
+        board.set_piece(Position::at(0, 3), Disc::Player1);
+        assert_eq!(board[Position::at(0, 3)], Disc::Player1);
+        assert_eq!(board.get_piece(Position::at(0, 3)), Disc::Player1);
+        assert_eq!(board[Position::at(3, 3)], Disc::Player2);
+        assert_eq!(board.get_piece(Position::at(3, 3)), Disc::Player2);
+        board.set_piece(Position::at(3, 3), Disc::Player1);
+        assert_eq!(board[Position::at(3, 3)], Disc::Player1);
+        assert_eq!(board.get_piece(Position::at(3, 3)), Disc::Player1);
+    }
+
+    #[test]
+    fn test_checked_getter() {
+        let board = Board::default();
+        assert_eq!(board.get(Position::at(3, 3)), Some(Disc::Player2));
+        assert_eq!(board.get((3u8, 4u8)), Some(Disc::Player1));
+        assert_eq!(board.get((0usize, 0usize)), Some(Disc::Empty));
+        assert_eq!(board.get(28u8), Some(Disc::Player1));
+        assert_eq!(board.get((8u8, 0u8)), None);
+        assert_eq!(board.get((0usize, 200usize)), None);
+        assert_eq!(board.get(64u8), None);
     }
 
     #[test]
     fn test_neighbours() {
+        use neighbours::Shape;
         let board = Board::default();
         assert!(matches!(
-            board.neighbours((0u8, 0u8)),
-            Neighbours::TopLeftCorner(_)
+            board.neighbours(Position::at(0, 0)).shape,
+            Shape::TopLeftCorner(_)
         ));
         assert!(matches!(
-            board.neighbours((0u8, 7u8)),
-            Neighbours::TopRightCorner(_)
+            board.neighbours(Position::at(0, 7)).shape,
+            Shape::TopRightCorner(_)
         ));
         assert!(matches!(
-            board.neighbours((7u8, 0u8)),
-            Neighbours::BottomLeftCorner(_)
+            board.neighbours(Position::at(7, 0)).shape,
+            Shape::BottomLeftCorner(_)
         ));
         assert!(matches!(
-            board.neighbours((7u8, 7u8)),
-            Neighbours::BottomRightCorner(_)
+            board.neighbours(Position::at(7, 7)).shape,
+            Shape::BottomRightCorner(_)
         ));
         assert!(matches!(
-            board.neighbours((0u8, 5u8)),
-            Neighbours::TopEdge(_)
+            board.neighbours(Position::at(0, 5)).shape,
+            Shape::TopEdge(_)
         ));
         assert!(matches!(
-            board.neighbours((4u8, 7u8)),
-            Neighbours::RightEdge(_)
+            board.neighbours(Position::at(4, 7)).shape,
+            Shape::RightEdge(_)
         ));
         assert!(matches!(
-            board.neighbours((7u8, 5u8)),
-            Neighbours::BottomEdge(_)
+            board.neighbours(Position::at(7, 5)).shape,
+            Shape::BottomEdge(_)
         ));
         assert!(matches!(
-            board.neighbours((5u8, 0u8)),
-            Neighbours::LeftEdge(_)
+            board.neighbours(Position::at(5, 0)).shape,
+            Shape::LeftEdge(_)
         ));
         assert!(matches!(
-            board.neighbours((4u8, 6u8)),
-            Neighbours::InnerPoint(_)
+            board.neighbours(Position::at(4, 6)).shape,
+            Shape::InnerPoint(_)
         ));
     }
 
@@ -393,35 +566,35 @@ mod tests {
     fn test_striders() {
         let board = Board::default();
         assert!(matches!(
-            board.get_points_in_line((3u8, 4u8), (2u8, 4u8)),
+            board.get_points_in_line(Position::at(3, 4), Position::at(2, 4)),
             Strider {dir: Direction::Up, ..}
         ));
         assert!(matches!(
-            board.get_points_in_line((3u8, 4u8), (2u8, 5u8)),
+            board.get_points_in_line(Position::at(3, 4), Position::at(2, 5)),
             Strider {dir: Direction::UpRight, ..}
         ));
         assert!(matches!(
-            board.get_points_in_line((3u8, 4u8), (3u8, 5u8)),
+            board.get_points_in_line(Position::at(3, 4), Position::at(3, 5)),
             Strider {dir: Direction::Right, ..}
         ));
         assert!(matches!(
-            board.get_points_in_line((3u8, 4u8), (4u8, 5u8)),
+            board.get_points_in_line(Position::at(3, 4), Position::at(4, 5)),
             Strider {dir: Direction::DownRight, ..}
         ));
         assert!(matches!(
-            board.get_points_in_line((3u8, 4u8), (4u8, 4u8)),
+            board.get_points_in_line(Position::at(3, 4), Position::at(4, 4)),
             Strider {dir: Direction::Down, ..}
         ));
         assert!(matches!(
-            board.get_points_in_line((3u8, 4u8), (4u8, 3u8)),
+            board.get_points_in_line(Position::at(3, 4), Position::at(4, 3)),
             Strider {dir: Direction::DownLeft, ..}
         ));
         assert!(matches!(
-            board.get_points_in_line((3u8, 4u8), (3u8, 3u8)),
+            board.get_points_in_line(Position::at(3, 4), Position::at(3, 3)),
             Strider {dir: Direction::Left, ..}
         ));
         assert!(matches!(
-            board.get_points_in_line((3u8, 4u8), (2u8, 3u8)),
+            board.get_points_in_line(Position::at(3, 4), Position::at(2, 3)),
             Strider {dir: Direction::UpLeft, ..}
         ));
     }
@@ -431,12 +604,12 @@ mod tests {
         let board = Board::default();
         let player1 = Player::Player1;
         let player2 = Player::Player2;
-        assert!(board.is_legal_move((2u8, 3u8), player1));
-        assert!(!board.is_legal_move((2u8, 3u8), player2));
-        assert!(board.is_legal_move((2u8, 4u8), player2));
-        assert!(!board.is_legal_move((2u8, 4u8), player1));
-        assert!(!board.is_legal_move((3u8, 4u8), player1));
-        assert!(!board.is_legal_move((3u8, 4u8), player2));
+        assert!(board.is_legal_move(Position::at(2, 3), player1));
+        assert!(!board.is_legal_move(Position::at(2, 3), player2));
+        assert!(board.is_legal_move(Position::at(2, 4), player2));
+        assert!(!board.is_legal_move(Position::at(2, 4), player1));
+        assert!(!board.is_legal_move(Position::at(3, 4), player1));
+        assert!(!board.is_legal_move(Position::at(3, 4), player2));
     }
 
     #[test]
@@ -447,16 +620,16 @@ mod tests {
         let mut moves_for_player1 = board.all_legal_moves(player1);
         let mut moves_for_player2 = board.all_legal_moves(player2);
 
-        assert_eq!(moves_for_player1.next(), Some((2u8, 3u8).into()));
-        assert_eq!(moves_for_player1.next(), Some((3u8, 2u8).into()));
-        assert_eq!(moves_for_player1.next(), Some((4u8, 5u8).into()));
-        assert_eq!(moves_for_player1.next(), Some((5u8, 4u8).into()));
+        assert_eq!(moves_for_player1.next(), Some(Position::at(2, 3)));
+        assert_eq!(moves_for_player1.next(), Some(Position::at(3, 2)));
+        assert_eq!(moves_for_player1.next(), Some(Position::at(4, 5)));
+        assert_eq!(moves_for_player1.next(), Some(Position::at(5, 4)));
         assert_eq!(moves_for_player1.next(), None);
 
-        assert_eq!(moves_for_player2.next(), Some((2u8, 4u8).into()));
-        assert_eq!(moves_for_player2.next(), Some((3u8, 5u8).into()));
-        assert_eq!(moves_for_player2.next(), Some((4u8, 2u8).into()));
-        assert_eq!(moves_for_player2.next(), Some((5u8, 3u8).into()));
+        assert_eq!(moves_for_player2.next(), Some(Position::at(2, 4)));
+        assert_eq!(moves_for_player2.next(), Some(Position::at(3, 5)));
+        assert_eq!(moves_for_player2.next(), Some(Position::at(4, 2)));
+        assert_eq!(moves_for_player2.next(), Some(Position::at(5, 3)));
         assert_eq!(moves_for_player2.next(), None);
     }
 
@@ -464,11 +637,417 @@ mod tests {
     fn test_piece_placing() {
         let mut board = Board::default();
         let player1 = Player::Player1;
-        assert_eq!(board[(3u8, 3u8)], Disc::Player2);
-        assert_eq!(board[(2u8, 3u8)], Disc::Empty);
-        let turned = board.place_piece((2u8, 3u8), player1);
-        assert_eq!(turned, vec![(3u8, 3u8).into()]);
-        assert_eq!(board[(3u8, 3u8)], Disc::Player1);
-        assert_eq!(board[(2u8, 3u8)], Disc::Player1);
+        assert_eq!(board[Position::at(3, 3)], Disc::Player2);
+        assert_eq!(board[Position::at(2, 3)], Disc::Empty);
+        let turned = board.place_piece(Position::at(2, 3), player1);
+        assert_eq!(turned.pos(), Position::at(2, 3));
+        assert_eq!(turned.player(), player1);
+        assert_eq!(
+            turned.flipped().collect::<Vec<_>>(),
+            vec![Position::at(3, 3)]
+        );
+        assert_eq!(board[Position::at(3, 3)], Disc::Player1);
+        assert_eq!(board[Position::at(2, 3)], Disc::Player1);
+    }
+
+    fn board_from(rows: [&str; 8]) -> Board {
+        rows.concat().parse().unwrap()
+    }
+
+    fn squares(bb: u64) -> Vec<String> {
+        Bits(bb).map(|idx| Position { idx }.to_string()).collect()
+    }
+
+    #[test]
+    fn test_stable_discs() {
+        let board = Board::default();
+        assert_eq!(board.stable_discs(Player::Player1), 0);
+        assert_eq!(Board::empty().stable_discs(Player::Player1), 0);
+
+        // A lone corner is stable, and so is an edge chain from it, but not past a gap or an
+        // opponent disc
+        let board = board_from([
+            "XXXO-XX-", "X-------", "X-------", "--------", "--------", "--------", "--------",
+            "-------O",
+        ]);
+        assert_eq!(
+            squares(board.stable_discs(Player::Player1)),
+            ["a1", "b1", "c1", "a2", "a3"]
+        );
+        assert_eq!(squares(board.stable_discs(Player::Player2)), ["h8"]);
+    }
+
+    #[test]
+    fn test_stable_full_lines() {
+        // A full edge is stable whatever its colours
+        let board = board_from([
+            "XOXOOXXO", "--------", "--------", "--------", "--------", "--------", "--------",
+            "--------",
+        ]);
+        assert_eq!(
+            squares(board.stable_discs(Player::Player1)),
+            ["a1", "c1", "f1", "g1"]
+        );
+        assert_eq!(
+            board.stable_discs(Player::Player1) | board.stable_discs(Player::Player2),
+            0xff
+        );
+
+        // Inside the board every line through the disc has to be full or anchored
+        let mut rows = ["--------"; 8];
+        rows[3] = "XXXXOXXX";
+        let board = board_from(rows);
+        assert_eq!(board.stable_discs(Player::Player2), 0);
+    }
+
+    #[test]
+    fn test_stable_x_square() {
+        // b2 is anchored on every line by a1, a2, b1 and one of c1 or a3
+        let board = board_from([
+            "XXX-----", "XX------", "X-------", "--------", "--------", "--------", "--------",
+            "--------",
+        ]);
+        assert_ne!(board.stable_discs(Player::Player1) & 1 << 9, 0);
+        let board = board_from([
+            "XXX-----", "XX------", "--------", "--------", "--------", "--------", "--------",
+            "--------",
+        ]);
+        assert_ne!(board.stable_discs(Player::Player1) & 1 << 9, 0);
+        let board = board_from([
+            "XX------", "XX------", "--------", "--------", "--------", "--------", "--------",
+            "--------",
+        ]);
+        assert_eq!(
+            squares(board.stable_discs(Player::Player1)),
+            ["a1", "b1", "a2"]
+        );
+        // An opponent disc on the open line does not protect b2 either
+        let board = board_from([
+            "XXO-----", "XX------", "--------", "--------", "--------", "--------", "--------",
+            "--------",
+        ]);
+        assert_eq!(
+            squares(board.stable_discs(Player::Player1)),
+            ["a1", "b1", "a2"]
+        );
+    }
+
+    #[test]
+    fn test_stable_discs_never_flip() {
+        let mut rng = Rng::new(0x9e37_79b9_7f4a_7c15);
+        for _ in 0..50 {
+            let mut game = RandomGame::new(&mut rng);
+            let mut stable = [0u64; 2];
+            loop {
+                let board = &game.board;
+                // Once stable, a disc keeps its colour for the rest of the game
+                assert_eq!(board.p1 & stable[0], stable[0]);
+                assert_eq!(board.p2 & stable[1], stable[1]);
+                stable[0] = board.stable_discs(Player::Player1);
+                stable[1] = board.stable_discs(Player::Player2);
+                if game.play().is_none() {
+                    break;
+                }
+            }
+            let board = game.board;
+            // Every disc of a full board is stable
+            if board.empty_squares() == 0 {
+                assert_eq!(stable[0], board.p1);
+                assert_eq!(stable[1], board.p2);
+            }
+        }
+    }
+
+    #[test]
+    fn test_transform() {
+        let mut board = Board::default();
+        board.place_piece(Position::at(2, 3), Player::Player1);
+        board.place_piece(Position::at(2, 2), Player::Player2);
+        for &sym in &Symmetry::ALL {
+            let moved = board.transform(sym);
+            for idx in 0..=MAX_VALID_POS {
+                let pos = Position { idx };
+                assert_eq!(moved[pos.transform(sym)], board[pos]);
+            }
+            let legal: u64 = board
+                .all_legal_moves(Player::Player1)
+                .map(|pos| 1 << pos.transform(sym).idx)
+                .sum();
+            assert_eq!(moved.legal_moves_mask(Player::Player1), legal);
+            assert_eq!(moved.transform(sym.inverse()), board);
+        }
+    }
+
+    #[test]
+    fn test_canonical() {
+        let mut board = Board::default();
+        board.place_piece(Position::at(2, 3), Player::Player1);
+        board.place_piece(Position::at(2, 2), Player::Player2);
+        let (canonical, _) = board.canonical();
+        for &sym in &Symmetry::ALL {
+            let moved = board.transform(sym);
+            let (other, to_canonical) = moved.canonical();
+            assert_eq!(other, canonical);
+            assert_eq!(moved.transform(to_canonical), canonical);
+            // A move found on the canonical board maps back to a legal move
+            for pos in canonical.all_legal_moves(Player::Player1) {
+                let back = pos.transform(to_canonical.inverse());
+                assert!(moved.is_legal_move(back, Player::Player1));
+            }
+        }
+    }
+
+    #[test]
+    fn test_unmake() {
+        let mut board = Board::default();
+        let first = board.place_piece(Position::at(2, 3), Player::Player1);
+        let after_first = board.clone();
+        let second = board.place_piece(Position::at(2, 2), Player::Player2);
+        assert_eq!(second.flip_count(), 1);
+        board.unmake(&second);
+        assert_eq!(board, after_first);
+        board.unmake(&first);
+        assert_eq!(board, Board::default());
+        assert_eq!(
+            board.zobrist_key(Player::Player1),
+            Board::default().zobrist_key(Player::Player1)
+        );
+    }
+
+    #[test]
+    fn test_try_piece_placing() {
+        let mut board = Board::default();
+        let player1 = Player::Player1;
+        assert_eq!(
+            board.try_place_piece(Position::at(3, 3), player1),
+            Err(MoveError::Occupied)
+        );
+        assert_eq!(
+            board.try_place_piece(Position::at(0, 0), player1),
+            Err(MoveError::NoFlips)
+        );
+        assert_eq!(board, Board::default());
+        let turned = board.try_place_piece(Position::at(2, 3), player1);
+        assert_eq!(turned.map(|mv| mv.flips()), Ok(1 << 27));
+        assert_eq!(board[Position::at(2, 3)], Disc::Player1);
+    }
+
+    #[test]
+    fn test_blocked_squares() {
+        let mut board = board_from([
+            "-O#OX---", "--------", "--------", "---OX---", "---XO---", "--------", "--------",
+            "--------",
+        ]);
+        let a1 = Position::at(0, 0);
+        let c1 = Position::at(0, 2);
+        assert_eq!(board[c1], Disc::Blocked);
+        assert_eq!(board.blocked_squares(), 1 << 2);
+        assert_eq!(board.iter().filter(|&d| d == Disc::Blocked).count(), 1);
+        assert!(board
+            .neighbours(Position::at(0, 1))
+            .any(|n| n == (c1, Disc::Blocked)));
+
+        // Nothing goes on c1, and the line from a1 through b1 stops there
+        assert!(!board.is_legal_move(c1, Player::Player1));
+        assert!(!board.is_legal_move(a1, Player::Player1));
+        assert_eq!(
+            board.legal_moves_mask(Player::Player1),
+            Board::default().legal_moves_mask(Player::Player1)
+        );
+        assert_eq!(board.place_piece_dry_run(a1, Player::Player1).flips(), 0);
+        assert_eq!(board.flips(c1, Player::Player1), 0);
+        assert_eq!(
+            board.try_place_piece(c1, Player::Player1),
+            Err(MoveError::Blocked)
+        );
+        let mut line = board.get_points_in_line(a1, Position::at(0, 1));
+        assert_eq!(line.next(), Some((Position::at(0, 1), Disc::Player2)));
+        assert_eq!(line.next(), None);
+        assert_eq!(line.next(), None);
+
+        let rotated = board.transform(Symmetry::Rotate180);
+        assert_eq!(rotated[c1.transform(Symmetry::Rotate180)], Disc::Blocked);
+        assert_eq!(rotated.transform(Symmetry::Rotate180), board);
+
+        board.set_blocked(1 << 3);
+        assert_eq!(board[c1], Disc::Empty);
+        assert_eq!(board[Position::at(0, 3)], Disc::Blocked);
+        assert_eq!(board.empty_squares().count_ones(), 64 - 7);
+        board.set_piece(c1, Disc::Blocked);
+        assert_eq!(board.blocked_squares(), 1 << 2 | 1 << 3);
+        board.set_piece(c1, Disc::Player1);
+        assert_eq!(board.blocked_squares(), 1 << 3);
+    }
+
+    #[test]
+    fn test_blocked_squares_hash() {
+        let mut board = Board::default();
+        let key = board.zobrist_key(Player::Player1);
+        board.set_blocked(1);
+        let blocked_key = board.zobrist_key(Player::Player1);
+        assert_ne!(blocked_key, key);
+        assert_eq!(board.hash, zobrist::hash(board.p1, board.p2, board.blocked));
+        board.set_blocked(1 << 1);
+        assert_ne!(board.zobrist_key(Player::Player1), blocked_key);
+        board.set_piece(Position::at(0, 1), Disc::Empty);
+        assert_eq!(board.zobrist_key(Player::Player1), key);
+        board.set_piece(Position::at(0, 0), Disc::Blocked);
+        assert_eq!(board.zobrist_key(Player::Player1), blocked_key);
+        let rotated = board.transform(Symmetry::Rotate90);
+        assert_eq!(
+            rotated.hash,
+            zobrist::hash(rotated.p1, rotated.p2, rotated.blocked)
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "blocked square")]
+    fn test_place_on_blocked_square() {
+        let mut board = Board::default();
+        board.set_blocked(1);
+        board.place_piece(Position::at(0, 0), Player::Player1);
+    }
+
+    #[test]
+    fn test_stable_next_to_blocked() {
+        // A lone edge disc is stable once both its neighbours on the edge are blocked
+        let mut board = board_from([
+            "---X----", "--------", "--------", "--------", "--------", "--------", "--------",
+            "--------",
+        ]);
+        assert_eq!(board.stable_discs(Player::Player1), 0);
+        board.set_blocked(1 << 2 | 1 << 4);
+        assert_eq!(squares(board.stable_discs(Player::Player1)), vec!["d1"]);
+    }
+
+    #[test]
+    fn test_legal_moves_mask() {
+        let board = Board::default();
+        assert_eq!(
+            board.legal_moves_mask(Player::Player1),
+            1 << 19 | 1 << 26 | 1 << 37 | 1 << 44
+        );
+        assert_eq!(
+            board.legal_moves_mask(Player::Player2),
+            1 << 20 | 1 << 29 | 1 << 34 | 1 << 43
+        );
+        assert_eq!(Board::empty().legal_moves_mask(Player::Player1), 0);
+    }
+
+    #[test]
+    fn test_flips() {
+        let board = Board::default();
+        assert_eq!(board.flips(Position::at(2, 3), Player::Player1), 1 << 27);
+        assert_eq!(board.flips(Position::at(2, 3), Player::Player2), 0);
+        assert_eq!(board.flips(Position::at(3, 3), Player::Player1), 0);
+
+        // A disc on d4 closing lines in several directions at once
+        let mut board = Board::empty();
+        for &(row, col) in &[(3, 1), (3, 2), (2, 2), (4, 4), (2, 3), (1, 3)] {
+            board.set_piece(Position::at(row, col), Disc::Player2);
+        }
+        for &(row, col) in &[(3, 0), (1, 1), (5, 5), (0, 3), (3, 7)] {
+            board.set_piece(Position::at(row, col), Disc::Player1);
+        }
+        let flips = board.flips(Position::at(3, 3), Player::Player1);
+        let expected = [(3, 1), (3, 2), (2, 2), (4, 4), (2, 3), (1, 3)]
+            .iter()
+            .fold(0, |acc, &(row, col)| acc | 1 << Position::at(row, col).idx);
+        assert_eq!(flips, expected);
+    }
+
+    #[test]
+    fn test_zobrist_is_incremental() {
+        let mut board = Board::default();
+        assert_eq!(board.hash, zobrist::hash(board.p1, board.p2, board.blocked));
+        board.place_piece(Position::at(2, 3), Player::Player1);
+        assert_eq!(board.hash, zobrist::hash(board.p1, board.p2, board.blocked));
+        board.set_piece(Position::at(3, 3), Disc::Player2);
+        board.set_piece(Position::at(7, 7), Disc::Player1);
+        board.set_piece(Position::at(4, 4), Disc::Empty);
+        assert_eq!(board.hash, zobrist::hash(board.p1, board.p2, board.blocked));
+        assert_ne!(
+            board.zobrist_key(Player::Player1),
+            board.zobrist_key(Player::Player2)
+        );
+    }
+
+    #[test]
+    fn test_transpositions_hash_equal() {
+        use std::collections::hash_map::DefaultHasher;
+
+        let mut a = Board::default();
+        a.place_piece(Position::at(2, 3), Player::Player1);
+        a.place_piece(Position::at(2, 2), Player::Player2);
+        a.place_piece(Position::at(3, 2), Player::Player1);
+        let mut b = Board::default();
+        b.place_piece(Position::at(3, 2), Player::Player1);
+        b.place_piece(Position::at(2, 2), Player::Player2);
+        b.place_piece(Position::at(2, 3), Player::Player1);
+        assert_eq!(a, b);
+        assert_eq!(
+            a.zobrist_key(Player::Player2),
+            b.zobrist_key(Player::Player2)
+        );
+
+        let hash = |board: &Board| {
+            let mut hasher = DefaultHasher::new();
+            board.hash(&mut hasher);
+            hasher.finish()
+        };
+        assert_eq!(hash(&a), hash(&b));
+        assert_ne!(hash(&a), hash(&Board::default()));
+    }
+
+    /// Move generation by walking neighbours and lines square by square
+    fn reference_flips(board: &Board, pos: Position, player: Player) -> Vec<Position> {
+        let mut turned = vec![];
+        if board[pos] != Disc::Empty {
+            return turned;
+        }
+        for (neighbour_pos, _) in board.neighbours(pos) {
+            let line: Vec<_> = board.get_points_in_line(pos, neighbour_pos).collect();
+            let run = line
+                .iter()
+                .take_while(|&&(_, d)| d == player.opponent())
+                .count();
+            if run > 0 && line.get(run).map(|&(_, d)| d == player) == Some(true) {
+                turned.extend(line[..run].iter().map(|&(p, _)| p));
+            }
+        }
+        turned.sort_by_key(|p| p.idx);
+        turned
+    }
+
+    #[test]
+    fn test_bitboards_match_reference_on_random_games() {
+        let mut rng = Rng::new(0x2545_f491_4f6c_dd1d);
+        for _ in 0..50 {
+            let mut game = RandomGame::new(&mut rng);
+            loop {
+                let (board, player) = (&game.board, game.player);
+                for idx in 0..=MAX_VALID_POS {
+                    let pos = Position { idx };
+                    let expected = reference_flips(board, pos, player);
+                    let mv = board.place_piece_dry_run(pos, player);
+                    assert_eq!(mv.flipped().collect::<Vec<_>>(), expected);
+                    assert_eq!(board.is_legal_move(pos, player), !expected.is_empty());
+                }
+                let count = game.board.player1_count() + game.board.player2_count();
+                let before = game.board.clone();
+                let mv = match game.play() {
+                    Some(mv) => mv,
+                    None => break,
+                };
+                let board = &game.board;
+                assert_eq!(board.player1_count() + board.player2_count(), count + 1);
+                assert!(mv.flipped().all(|p| board[p] == mv.player()));
+                assert_eq!(board.hash, zobrist::hash(board.p1, board.p2, board.blocked));
+                let mut undone = board.clone();
+                undone.unmake(&mv);
+                assert_eq!(undone, before);
+                assert_eq!(undone.hash, before.hash);
+            }
+        }
     }
 }
@@ -0,0 +1,224 @@
+//! Shift-and-mask helpers for the `u64` bitboards backing [`Board`](crate::Board).
+//!
+//! Bit `row * 8 + col` of a bitboard stands for the square at `(row, col)`, the same index
+//! [`Position`](crate::Position) uses.
+
+use crate::board::Direction;
+
+pub(crate) const FILE_A: u64 = 0x0101_0101_0101_0101;
+pub(crate) const FILE_H: u64 = 0x8080_8080_8080_8080;
+
+impl Direction {
+    pub const ALL: [Direction; 8] = [
+        Direction::Up,
+        Direction::UpRight,
+        Direction::Right,
+        Direction::DownRight,
+        Direction::Down,
+        Direction::DownLeft,
+        Direction::Left,
+        Direction::UpLeft,
+    ];
+
+    pub const fn opposite(self) -> Direction {
+        match self {
+            Direction::Up => Direction::Down,
+            Direction::UpRight => Direction::DownLeft,
+            Direction::Right => Direction::Left,
+            Direction::DownRight => Direction::UpLeft,
+            Direction::Down => Direction::Up,
+            Direction::DownLeft => Direction::UpRight,
+            Direction::Left => Direction::Right,
+            Direction::UpLeft => Direction::DownRight,
+        }
+    }
+
+    /// Moves every square of `bb` one step in this direction, dropping squares that fall
+    /// off the board instead of wrapping them onto the next row.
+    #[inline]
+    pub(crate) const fn shift(self, bb: u64) -> u64 {
+        match self {
+            Direction::Up => bb >> 8,
+            Direction::UpRight => (bb >> 7) & !FILE_A,
+            Direction::Right => (bb << 1) & !FILE_A,
+            Direction::DownRight => (bb << 9) & !FILE_A,
+            Direction::Down => bb << 8,
+            Direction::DownLeft => (bb << 7) & !FILE_H,
+            Direction::Left => (bb >> 1) & !FILE_H,
+            Direction::UpLeft => (bb >> 9) & !FILE_H,
+        }
+    }
+}
+
+/// Every empty square from which a run of `opponent` discs in `dir` ends in a `player` disc.
+#[inline]
+pub(crate) fn moves_in_direction(player: u64, opponent: u64, dir: Direction) -> u64 {
+    let empty = !(player | opponent);
+    let mut run = dir.shift(player) & opponent;
+    for _ in 0..5 {
+        run |= dir.shift(run) & opponent;
+    }
+    dir.shift(run) & empty
+}
+
+/// The `opponent` discs flanked in `dir` by a disc placed on `mv` and an existing `player` disc.
+#[inline]
+pub(crate) fn flips_in_direction(mv: u64, player: u64, opponent: u64, dir: Direction) -> u64 {
+    let mut run = dir.shift(mv) & opponent;
+    for _ in 0..5 {
+        run |= dir.shift(run) & opponent;
+    }
+    if dir.shift(run) & player != 0 {
+        run
+    } else {
+        0
+    }
+}
+
+/// Every empty square `player` can move to against `opponent`.
+#[inline]
+pub(crate) fn moves(player: u64, opponent: u64) -> u64 {
+    Direction::ALL.iter().fold(0, |acc, &dir| {
+        acc | moves_in_direction(player, opponent, dir)
+    })
+}
+
+/// The `opponent` discs flipped by a `player` disc placed on the empty square `idx`.
+#[inline]
+pub(crate) fn flips(idx: u8, player: u64, opponent: u64) -> u64 {
+    let mv = 1 << idx;
+    Direction::ALL.iter().fold(0, |acc, &dir| {
+        acc | flips_in_direction(mv, player, opponent, dir)
+    })
+}
+
+/// Every square adjacent to a square of `bb`, including squares of `bb` itself that touch
+/// another one.
+#[inline]
+pub(crate) fn adjacent(bb: u64) -> u64 {
+    Direction::ALL
+        .iter()
+        .fold(0, |acc, &dir| acc | dir.shift(bb))
+}
+
+/// The four lines through a square, each as one of its two directions.
+const AXES: [Direction; 4] = [
+    Direction::Right,
+    Direction::Down,
+    Direction::DownRight,
+    Direction::DownLeft,
+];
+
+/// Every square from which all squares in `dir` up to the edge are in `occupied`, the square
+/// itself included.
+#[inline]
+fn filled_towards(occupied: u64, dir: Direction) -> u64 {
+    // Squares on the edge in `dir`, which have no neighbour that way
+    let edge = !dir.opposite().shift(!0);
+    let mut filled = occupied & edge;
+    for _ in 0..7 {
+        filled |= occupied & dir.opposite().shift(filled);
+    }
+    filled
+}
+
+/// The `player` discs that `opponent` can never flip. A disc is stable when, along each of
+/// the four lines through it, the line is full or one of its neighbours on the line is the
+/// edge, a `blocked` square or a stable disc of its own colour. Starting from none, stable
+/// discs are added until nothing changes, which anchors them on corners and grows chains
+/// along the edges.
+pub(crate) fn stable(player: u64, opponent: u64, blocked: u64) -> u64 {
+    let occupied = player | opponent | blocked;
+    let mut safe_axes = [0u64; 4];
+    for (safe, &dir) in safe_axes.iter_mut().zip(AXES.iter()) {
+        let full = filled_towards(occupied, dir) & filled_towards(occupied, dir.opposite());
+        let edges = !dir.shift(!blocked) | !dir.opposite().shift(!blocked);
+        *safe = full | edges;
+    }
+
+    let mut stable = 0;
+    loop {
+        let mut next = player;
+        for (&safe, &dir) in safe_axes.iter().zip(AXES.iter()) {
+            let anchored = dir.shift(stable) | dir.opposite().shift(stable);
+            next &= safe | anchored;
+        }
+        if next == stable {
+            return stable;
+        }
+        stable = next;
+    }
+}
+
+/// Iterates over the set bits of a bitboard, lowest first.
+#[derive(Debug, Clone)]
+pub(crate) struct Bits(pub(crate) u64);
+
+impl Iterator for Bits {
+    type Item = u8;
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.0 == 0 {
+            return None;
+        }
+        let idx = self.0.trailing_zeros() as u8;
+        self.0 &= self.0 - 1;
+        Some(idx)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.0.count_ones() as usize;
+        (len, Some(len))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shift_does_not_wrap() {
+        assert_eq!(Direction::Right.shift(FILE_H), 0);
+        assert_eq!(Direction::Left.shift(FILE_A), 0);
+        assert_eq!(Direction::Up.shift(0xff), 0);
+        assert_eq!(Direction::Down.shift(0xff << 56), 0);
+        assert_eq!(Direction::UpRight.shift(1 << 15), 0);
+        assert_eq!(Direction::DownLeft.shift(1 << 8), 0);
+        assert_eq!(Direction::DownRight.shift(1 << 9), 1 << 18);
+        assert_eq!(Direction::UpLeft.shift(1 << 18), 1 << 9);
+    }
+
+    #[test]
+    fn test_opposite() {
+        for &dir in &Direction::ALL {
+            assert_ne!(dir.opposite(), dir);
+            assert_eq!(dir.opposite().opposite(), dir);
+            assert_eq!(dir.opposite().shift(dir.shift(1 << 27)), 1 << 27);
+        }
+    }
+
+    #[test]
+    fn test_filled_towards() {
+        // Row 0 full up to h1, row 1 with a hole at a2
+        let occupied = 0xff | 0xfe << 8;
+        assert_eq!(
+            filled_towards(occupied, Direction::Right),
+            0xffff & !(1 << 8)
+        );
+        assert_eq!(filled_towards(occupied, Direction::Left), 0xff);
+    }
+
+    #[test]
+    fn test_adjacent() {
+        assert_eq!(adjacent(1), 1 << 1 | 1 << 8 | 1 << 9);
+        assert_eq!(adjacent(1 << 63), 1 << 62 | 1 << 55 | 1 << 54);
+        assert_eq!(adjacent(1 << 27).count_ones(), 8);
+    }
+
+    #[test]
+    fn test_bits() {
+        let bits: Vec<u8> = Bits(0b1010_0001 | 1 << 63).collect();
+        assert_eq!(bits, vec![0, 5, 7, 63]);
+        assert_eq!(Bits(0).next(), None);
+    }
+}
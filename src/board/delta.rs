@@ -0,0 +1,41 @@
+use super::bitboard::Bits;
+use super::Player;
+use crate::position::Position;
+
+/// What a move changed on the board: the square played, who played it and the discs it
+/// flipped. [`Board::unmake`](super::Board::unmake) uses it to take the move back.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Move {
+    pos: Position,
+    player: Player,
+    /// Bitboard of the flipped discs
+    flips: u64,
+}
+
+impl Move {
+    pub(crate) fn new(pos: Position, player: Player, flips: u64) -> Self {
+        Self { pos, player, flips }
+    }
+
+    pub fn pos(&self) -> Position {
+        self.pos
+    }
+
+    pub fn player(&self) -> Player {
+        self.player
+    }
+
+    /// Bitboard of the flipped discs.
+    pub fn flips(&self) -> u64 {
+        self.flips
+    }
+
+    pub fn flip_count(&self) -> usize {
+        self.flips.count_ones() as usize
+    }
+
+    /// The flipped positions, in ascending order.
+    pub fn flipped(&self) -> impl Iterator<Item = Position> {
+        Bits(self.flips).map(|idx| Position { idx })
+    }
+}
@@ -1,8 +1,10 @@
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub enum Disc {
     Empty,
     Player1,
     Player2,
+    /// A hole in the board that no disc may occupy and that breaks lines
+    Blocked,
 }
 
 impl PartialEq<Player> for Disc {
@@ -23,7 +25,7 @@ impl From<Player> for Disc {
     }
 }
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
 pub enum Player {
     Player1,
     Player2,
@@ -0,0 +1,226 @@
+use super::{Board, Disc, Player};
+use crate::position::Position;
+
+/// A group of empty squares connected through their neighbours, horizontally, vertically or
+/// diagonally.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct EmptyRegion {
+    /// Bitboard of the squares in the region
+    pub squares: u64,
+}
+
+impl EmptyRegion {
+    pub fn size(&self) -> usize {
+        self.squares.count_ones() as usize
+    }
+
+    /// Whether the region has an odd number of squares. The side that moves first into an
+    /// odd region can usually also move last there.
+    pub fn is_odd(&self) -> bool {
+        self.size() % 2 == 1
+    }
+
+    pub fn contains(&self, pos: Position) -> bool {
+        self.squares >> pos.idx & 1 == 1
+    }
+}
+
+/// Positional features of a board with a given side to move. Squares are given as bitboards
+/// like [`Board::discs`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PositionFeatures {
+    to_move: Player,
+    /// Indexed by player 1 then player 2
+    frontier: [u64; 2],
+    mobility: [u64; 2],
+    potential_mobility: [u64; 2],
+    regions: Vec<EmptyRegion>,
+    quiet_moves: u64,
+}
+
+fn index(player: Player) -> usize {
+    match player {
+        Player::Player1 => 0,
+        Player::Player2 => 1,
+    }
+}
+
+impl PositionFeatures {
+    pub fn to_move(&self) -> Player {
+        self.to_move
+    }
+
+    /// The discs of `player` next to an empty square.
+    pub fn frontier(&self, player: Player) -> u64 {
+        self.frontier[index(player)]
+    }
+
+    /// The squares `player` can move to now.
+    pub fn mobility(&self, player: Player) -> u64 {
+        self.mobility[index(player)]
+    }
+
+    /// The empty squares next to an opponent disc, which `player` might be able to move to
+    /// later on.
+    pub fn potential_mobility(&self, player: Player) -> u64 {
+        self.potential_mobility[index(player)]
+    }
+
+    /// The empty regions, ordered by their lowest square.
+    pub fn regions(&self) -> &[EmptyRegion] {
+        &self.regions
+    }
+
+    /// The region holding `pos`, if it is empty.
+    pub fn region_of(&self, pos: Position) -> Option<&EmptyRegion> {
+        self.regions.iter().find(|region| region.contains(pos))
+    }
+
+    /// The legal moves of the side to move that flip no disc next to an empty square, so
+    /// that they open up no new squares for the opponent.
+    pub fn quiet_moves(&self) -> u64 {
+        self.quiet_moves
+    }
+
+    pub fn is_quiet(&self, pos: Position) -> bool {
+        self.quiet_moves >> pos.idx & 1 == 1
+    }
+}
+
+impl Board {
+    /// Works out the [`PositionFeatures`] of this board with `to_move` to play.
+    pub fn features(&self, to_move: Player) -> PositionFeatures {
+        let mut frontier = [0; 2];
+        let mut potential_mobility = [0; 2];
+        for idx in 0..64 {
+            let pos = Position { idx };
+            let touches = |disc: Disc| self.neighbours(pos).any(|(_, d)| d == disc);
+            match self[pos] {
+                Disc::Player1 if touches(Disc::Empty) => frontier[0] |= 1 << idx,
+                Disc::Player2 if touches(Disc::Empty) => frontier[1] |= 1 << idx,
+                Disc::Empty => {
+                    if touches(Disc::Player2) {
+                        potential_mobility[0] |= 1 << idx;
+                    }
+                    if touches(Disc::Player1) {
+                        potential_mobility[1] |= 1 << idx;
+                    }
+                }
+                _ => (),
+            }
+        }
+
+        let mobility = [
+            self.legal_moves_mask(Player::Player1),
+            self.legal_moves_mask(Player::Player2),
+        ];
+
+        let mut regions = vec![];
+        let mut unseen = self.empty_squares();
+        while unseen != 0 {
+            let start = Position {
+                idx: unseen.trailing_zeros() as u8,
+            };
+            let mut squares = 0u64;
+            let mut stack = vec![start];
+            while let Some(pos) = stack.pop() {
+                if squares >> pos.idx & 1 == 1 {
+                    continue;
+                }
+                squares |= 1 << pos.idx;
+                stack.extend(
+                    self.neighbours(pos)
+                        .filter(|&(_, d)| d == Disc::Empty)
+                        .map(|(p, _)| p),
+                );
+            }
+            unseen &= !squares;
+            regions.push(EmptyRegion { squares });
+        }
+
+        let mut quiet_moves = 0;
+        for pos in self.all_legal_moves(to_move) {
+            let mut after = self.clone();
+            let mv = after.place_piece(pos, to_move);
+            let opens = mv
+                .flipped()
+                .any(|p| after.neighbours(p).any(|(_, d)| d == Disc::Empty));
+            if !opens {
+                quiet_moves |= 1 << pos.idx;
+            }
+        }
+
+        PositionFeatures {
+            to_move,
+            frontier,
+            mobility,
+            potential_mobility,
+            regions,
+            quiet_moves,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sq(s: &str) -> Position {
+        s.parse().unwrap()
+    }
+
+    fn bb(squares: &[&str]) -> u64 {
+        squares.iter().fold(0, |acc, s| acc | 1 << sq(s).idx)
+    }
+
+    #[test]
+    fn test_start_position() {
+        let features = Board::default().features(Player::Player1);
+        assert_eq!(features.to_move(), Player::Player1);
+        assert_eq!(features.frontier(Player::Player1), bb(&["e4", "d5"]));
+        assert_eq!(features.frontier(Player::Player2), bb(&["d4", "e5"]));
+        assert_eq!(
+            features.mobility(Player::Player1),
+            bb(&["d3", "c4", "f5", "e6"])
+        );
+        assert_eq!(features.mobility(Player::Player2).count_ones(), 4);
+        assert_eq!(
+            features.potential_mobility(Player::Player1).count_ones(),
+            10
+        );
+        assert_eq!(features.regions().len(), 1);
+        assert_eq!(features.regions()[0].size(), 60);
+        assert!(!features.regions()[0].is_odd());
+        // Every opening move flips a disc that stays next to empty squares
+        assert_eq!(features.quiet_moves(), 0);
+    }
+
+    #[test]
+    fn test_regions_and_quiet_moves() {
+        // Everything is filled but for a1, b1 and a2 in one corner and h8 in another
+        let mut rows = ["OOOOOOOO"; 8];
+        rows[0] = "--XOOOOO";
+        rows[1] = "-XOOOOOO";
+        rows[7] = "OOOOOOX-";
+        let board: Board = rows.concat().parse().unwrap();
+        let features = board.features(Player::Player2);
+
+        let regions = features.regions();
+        assert_eq!(regions.len(), 2);
+        assert_eq!(regions[0].squares, bb(&["a1", "b1", "a2"]));
+        assert!(regions[0].is_odd());
+        assert_eq!(regions[1].size(), 1);
+        assert_eq!(features.region_of(sq("h8")), Some(&regions[1]));
+        assert_eq!(features.region_of(sq("c1")), None);
+
+        // h8 flips only g8, which no longer touches an empty square, unlike the flips into
+        // the a1 corner
+        assert!(features.is_quiet(sq("h8")));
+        assert!(!features.is_quiet(sq("a1")));
+        assert_eq!(
+            features.quiet_moves() & !features.mobility(Player::Player2),
+            0
+        );
+        assert_eq!(features.frontier(Player::Player1), bb(&["c1", "b2", "g8"]));
+    }
+}
@@ -1,8 +1,15 @@
 use crate::board::{Board, Disc};
 use crate::position::Position;
 
+/// The squares around a position, clockwise, leaving out those off the board.
 #[derive(Debug)]
-pub enum Neighbours<'a> {
+pub struct Neighbours<'a> {
+    pub(super) shape: Shape<'a>,
+}
+
+/// Where the position lies on the board, which decides the neighbours to visit
+#[derive(Debug)]
+pub(super) enum Shape<'a> {
     TopLeftCorner(TopLeftCorner<'a>),
     TopRightCorner(TopRightCorner<'a>),
     BottomLeftCorner(BottomLeftCorner<'a>),
@@ -15,82 +22,92 @@ pub enum Neighbours<'a> {
 }
 
 impl<'a> Neighbours<'a> {
-    pub fn top_left_corner(board: &'a Board) -> Self {
-        Neighbours::TopLeftCorner(TopLeftCorner { board, count: 0 })
+    fn new(shape: Shape<'a>) -> Self {
+        Self { shape }
     }
 
-    pub fn top_right_corner(board: &'a Board) -> Self {
-        Neighbours::TopRightCorner(TopRightCorner { board, count: 0 })
+    pub(super) fn top_left_corner(board: &'a Board) -> Self {
+        Self::new(Shape::TopLeftCorner(TopLeftCorner { board, count: 0 }))
     }
 
-    pub fn bottom_left_corner(board: &'a Board) -> Self {
-        Neighbours::BottomLeftCorner(BottomLeftCorner { board, count: 0 })
+    pub(super) fn top_right_corner(board: &'a Board) -> Self {
+        Self::new(Shape::TopRightCorner(TopRightCorner { board, count: 0 }))
     }
 
-    pub fn bottom_right_corner(board: &'a Board) -> Self {
-        Neighbours::BottomRightCorner(BottomRightCorner { board, count: 0 })
+    pub(super) fn bottom_left_corner(board: &'a Board) -> Self {
+        Self::new(Shape::BottomLeftCorner(BottomLeftCorner {
+            board,
+            count: 0,
+        }))
+    }
+
+    pub(super) fn bottom_right_corner(board: &'a Board) -> Self {
+        Self::new(Shape::BottomRightCorner(BottomRightCorner {
+            board,
+            count: 0,
+        }))
     }
 
-    pub fn top_edge(board: &'a Board, pos: Position) -> Self {
-        Neighbours::TopEdge(TopEdge {
+    pub(super) fn top_edge(board: &'a Board, pos: Position) -> Self {
+        Self::new(Shape::TopEdge(TopEdge {
             board,
             pos,
             count: 0,
-        })
+        }))
     }
 
-    pub fn right_edge(board: &'a Board, pos: Position) -> Self {
-        Neighbours::RightEdge(RightEdge {
+    pub(super) fn right_edge(board: &'a Board, pos: Position) -> Self {
+        Self::new(Shape::RightEdge(RightEdge {
             board,
             pos,
             count: 0,
-        })
+        }))
     }
 
-    pub fn bottom_edge(board: &'a Board, pos: Position) -> Self {
-        Neighbours::BottomEdge(BottomEdge {
+    pub(super) fn bottom_edge(board: &'a Board, pos: Position) -> Self {
+        Self::new(Shape::BottomEdge(BottomEdge {
             board,
             pos,
             count: 0,
-        })
+        }))
     }
 
-    pub fn left_edge(board: &'a Board, pos: Position) -> Self {
-        Neighbours::LeftEdge(LeftEdge {
+    pub(super) fn left_edge(board: &'a Board, pos: Position) -> Self {
+        Self::new(Shape::LeftEdge(LeftEdge {
             board,
             pos,
             count: 0,
-        })
+        }))
     }
 
-    pub fn inner_point(board: &'a Board, pos: Position) -> Self {
-        Neighbours::InnerPoint(InnerPoint {
+    pub(super) fn inner_point(board: &'a Board, pos: Position) -> Self {
+        Self::new(Shape::InnerPoint(InnerPoint {
             board,
             pos,
             count: 0,
-        })
+        }))
     }
 }
 
 impl<'a> Iterator for Neighbours<'a> {
     type Item = (Position, Disc);
     fn next(&mut self) -> Option<Self::Item> {
-        match self {
-            Neighbours::TopLeftCorner(a) => a.next(),
-            Neighbours::TopRightCorner(a) => a.next(),
-            Neighbours::BottomLeftCorner(a) => a.next(),
-            Neighbours::BottomRightCorner(a) => a.next(),
-            Neighbours::TopEdge(a) => a.next(),
-            Neighbours::RightEdge(a) => a.next(),
-            Neighbours::BottomEdge(a) => a.next(),
-            Neighbours::LeftEdge(a) => a.next(),
-            Neighbours::InnerPoint(a) => a.next(),
+        match &mut self.shape {
+            Shape::TopLeftCorner(a) => a.next(),
+            Shape::TopRightCorner(a) => a.next(),
+            Shape::BottomLeftCorner(a) => a.next(),
+            Shape::BottomRightCorner(a) => a.next(),
+            Shape::TopEdge(a) => a.next(),
+            Shape::RightEdge(a) => a.next(),
+            Shape::BottomEdge(a) => a.next(),
+            Shape::LeftEdge(a) => a.next(),
+            Shape::InnerPoint(a) => a.next(),
         }
     }
 }
 
 #[derive(Debug)]
-pub struct TopLeftCorner<'a> {
+pub(super) struct TopLeftCorner<'a> {
     board: &'a Board,
     count: u8,
 }
@@ -105,12 +122,13 @@ impl<'a> Iterator for TopLeftCorner<'a> {
             _ => return None,
         };
         self.count += 1;
-        Some((out_idx.into(), self.board[out_idx]))
+        let pos = Position::at(out_idx.0, out_idx.1);
+        Some((pos, self.board[pos]))
     }
 }
 
 #[derive(Debug)]
-pub struct TopRightCorner<'a> {
+pub(super) struct TopRightCorner<'a> {
     board: &'a Board,
     count: u8,
 }
@@ -125,12 +143,13 @@ impl<'a> Iterator for TopRightCorner<'a> {
             _ => return None,
         };
         self.count += 1;
-        Some((out_idx.into(), self.board[out_idx]))
+        let pos = Position::at(out_idx.0, out_idx.1);
+        Some((pos, self.board[pos]))
     }
 }
 
 #[derive(Debug)]
-pub struct BottomLeftCorner<'a> {
+pub(super) struct BottomLeftCorner<'a> {
     board: &'a Board,
     count: u8,
 }
@@ -145,12 +164,13 @@ impl<'a> Iterator for BottomLeftCorner<'a> {
             _ => return None,
         };
         self.count += 1;
-        Some((out_idx.into(), self.board[out_idx]))
+        let pos = Position::at(out_idx.0, out_idx.1);
+        Some((pos, self.board[pos]))
     }
 }
 
 #[derive(Debug)]
-pub struct BottomRightCorner<'a> {
+pub(super) struct BottomRightCorner<'a> {
     board: &'a Board,
     count: u8,
 }
@@ -165,12 +185,13 @@ impl<'a> Iterator for BottomRightCorner<'a> {
             _ => return None,
         };
         self.count += 1;
-        Some((out_idx.into(), self.board[out_idx]))
+        let pos = Position::at(out_idx.0, out_idx.1);
+        Some((pos, self.board[pos]))
     }
 }
 
 #[derive(Debug)]
-pub struct TopEdge<'a> {
+pub(super) struct TopEdge<'a> {
     board: &'a Board,
     pos: Position,
     count: u8,
@@ -188,12 +209,13 @@ impl<'a> Iterator for TopEdge<'a> {
             _ => return None,
         };
         self.count += 1;
-        Some((out_idx.into(), self.board[out_idx]))
+        let pos = Position::at(out_idx.0, out_idx.1);
+        Some((pos, self.board[pos]))
     }
 }
 
 #[derive(Debug)]
-pub struct RightEdge<'a> {
+pub(super) struct RightEdge<'a> {
     board: &'a Board,
     pos: Position,
     count: u8,
@@ -211,12 +233,13 @@ impl<'a> Iterator for RightEdge<'a> {
             _ => return None,
         };
         self.count += 1;
-        Some((out_idx.into(), self.board[out_idx]))
+        let pos = Position::at(out_idx.0, out_idx.1);
+        Some((pos, self.board[pos]))
     }
 }
 
 #[derive(Debug)]
-pub struct BottomEdge<'a> {
+pub(super) struct BottomEdge<'a> {
     board: &'a Board,
     pos: Position,
     count: u8,
@@ -234,12 +257,13 @@ impl<'a> Iterator for BottomEdge<'a> {
             _ => return None,
         };
         self.count += 1;
-        Some((out_idx.into(), self.board[out_idx]))
+        let pos = Position::at(out_idx.0, out_idx.1);
+        Some((pos, self.board[pos]))
     }
 }
 
 #[derive(Debug)]
-pub struct LeftEdge<'a> {
+pub(super) struct LeftEdge<'a> {
     board: &'a Board,
     pos: Position,
     count: u8,
@@ -257,12 +281,13 @@ impl<'a> Iterator for LeftEdge<'a> {
             _ => return None,
         };
         self.count += 1;
-        Some((out_idx.into(), self.board[out_idx]))
+        let pos = Position::at(out_idx.0, out_idx.1);
+        Some((pos, self.board[pos]))
     }
 }
 
 #[derive(Debug)]
-pub struct InnerPoint<'a> {
+pub(super) struct InnerPoint<'a> {
     board: &'a Board,
     pos: Position,
     count: u8,
@@ -283,7 +308,8 @@ impl<'a> Iterator for InnerPoint<'a> {
             _ => return None,
         };
         self.count += 1;
-        Some((out_idx.into(), self.board[out_idx]))
+        let pos = Position::at(out_idx.0, out_idx.1);
+        Some((pos, self.board[pos]))
     }
 }
 
@@ -295,9 +321,9 @@ mod tests {
     fn top_left() {
         let board = Board::default();
         let mut neighbours = Neighbours::top_left_corner(&board);
-        assert_eq!(neighbours.next(), Some(((0u8, 1u8).into(), Disc::Empty)));
-        assert_eq!(neighbours.next(), Some(((1u8, 1u8).into(), Disc::Empty)));
-        assert_eq!(neighbours.next(), Some(((1u8, 0u8).into(), Disc::Empty)));
+        assert_eq!(neighbours.next(), Some((Position::at(0, 1), Disc::Empty)));
+        assert_eq!(neighbours.next(), Some((Position::at(1, 1), Disc::Empty)));
+        assert_eq!(neighbours.next(), Some((Position::at(1, 0), Disc::Empty)));
         assert_eq!(neighbours.next(), None);
     }
 
@@ -305,9 +331,9 @@ mod tests {
     fn top_right() {
         let board = Board::default();
         let mut neighbours = Neighbours::top_right_corner(&board);
-        assert_eq!(neighbours.next(), Some(((1u8, 7u8).into(), Disc::Empty)));
-        assert_eq!(neighbours.next(), Some(((1u8, 6u8).into(), Disc::Empty)));
-        assert_eq!(neighbours.next(), Some(((0u8, 6u8).into(), Disc::Empty)));
+        assert_eq!(neighbours.next(), Some((Position::at(1, 7), Disc::Empty)));
+        assert_eq!(neighbours.next(), Some((Position::at(1, 6), Disc::Empty)));
+        assert_eq!(neighbours.next(), Some((Position::at(0, 6), Disc::Empty)));
         assert_eq!(neighbours.next(), None);
     }
 
@@ -315,9 +341,9 @@ mod tests {
     fn bot_left() {
         let board = Board::default();
         let mut neighbours = Neighbours::bottom_left_corner(&board);
-        assert_eq!(neighbours.next(), Some(((6u8, 0u8).into(), Disc::Empty)));
-        assert_eq!(neighbours.next(), Some(((6u8, 1u8).into(), Disc::Empty)));
-        assert_eq!(neighbours.next(), Some(((7u8, 1u8).into(), Disc::Empty)));
+        assert_eq!(neighbours.next(), Some((Position::at(6, 0), Disc::Empty)));
+        assert_eq!(neighbours.next(), Some((Position::at(6, 1), Disc::Empty)));
+        assert_eq!(neighbours.next(), Some((Position::at(7, 1), Disc::Empty)));
         assert_eq!(neighbours.next(), None);
     }
 
@@ -325,72 +351,72 @@ mod tests {
     fn bot_right() {
         let board = Board::default();
         let mut neighbours = Neighbours::bottom_right_corner(&board);
-        assert_eq!(neighbours.next(), Some(((6u8, 7u8).into(), Disc::Empty)));
-        assert_eq!(neighbours.next(), Some(((7u8, 6u8).into(), Disc::Empty)));
-        assert_eq!(neighbours.next(), Some(((6u8, 6u8).into(), Disc::Empty)));
+        assert_eq!(neighbours.next(), Some((Position::at(6, 7), Disc::Empty)));
+        assert_eq!(neighbours.next(), Some((Position::at(7, 6), Disc::Empty)));
+        assert_eq!(neighbours.next(), Some((Position::at(6, 6), Disc::Empty)));
         assert_eq!(neighbours.next(), None);
     }
 
     #[test]
     fn top_edge() {
         let board = Board::default();
-        let mut neighbours = Neighbours::top_edge(&board, (0u8, 4u8).into());
-        assert_eq!(neighbours.next(), Some(((0u8, 5u8).into(), Disc::Empty)));
-        assert_eq!(neighbours.next(), Some(((1u8, 5u8).into(), Disc::Empty)));
-        assert_eq!(neighbours.next(), Some(((1u8, 4u8).into(), Disc::Empty)));
-        assert_eq!(neighbours.next(), Some(((1u8, 3u8).into(), Disc::Empty)));
-        assert_eq!(neighbours.next(), Some(((0u8, 3u8).into(), Disc::Empty)));
+        let mut neighbours = Neighbours::top_edge(&board, Position::at(0, 4));
+        assert_eq!(neighbours.next(), Some((Position::at(0, 5), Disc::Empty)));
+        assert_eq!(neighbours.next(), Some((Position::at(1, 5), Disc::Empty)));
+        assert_eq!(neighbours.next(), Some((Position::at(1, 4), Disc::Empty)));
+        assert_eq!(neighbours.next(), Some((Position::at(1, 3), Disc::Empty)));
+        assert_eq!(neighbours.next(), Some((Position::at(0, 3), Disc::Empty)));
         assert_eq!(neighbours.next(), None);
     }
 
     #[test]
     fn right_edge() {
         let board = Board::default();
-        let mut neighbours = Neighbours::right_edge(&board, (4u8, 7u8).into());
-        assert_eq!(neighbours.next(), Some(((3u8, 7u8).into(), Disc::Empty)));
-        assert_eq!(neighbours.next(), Some(((5u8, 7u8).into(), Disc::Empty)));
-        assert_eq!(neighbours.next(), Some(((5u8, 6u8).into(), Disc::Empty)));
-        assert_eq!(neighbours.next(), Some(((4u8, 6u8).into(), Disc::Empty)));
-        assert_eq!(neighbours.next(), Some(((3u8, 6u8).into(), Disc::Empty)));
+        let mut neighbours = Neighbours::right_edge(&board, Position::at(4, 7));
+        assert_eq!(neighbours.next(), Some((Position::at(3, 7), Disc::Empty)));
+        assert_eq!(neighbours.next(), Some((Position::at(5, 7), Disc::Empty)));
+        assert_eq!(neighbours.next(), Some((Position::at(5, 6), Disc::Empty)));
+        assert_eq!(neighbours.next(), Some((Position::at(4, 6), Disc::Empty)));
+        assert_eq!(neighbours.next(), Some((Position::at(3, 6), Disc::Empty)));
         assert_eq!(neighbours.next(), None);
     }
 
     #[test]
     fn bot_edge() {
         let board = Board::default();
-        let mut neighbours = Neighbours::bottom_edge(&board, (7u8, 4u8).into());
-        assert_eq!(neighbours.next(), Some(((6u8, 4u8).into(), Disc::Empty)));
-        assert_eq!(neighbours.next(), Some(((6u8, 5u8).into(), Disc::Empty)));
-        assert_eq!(neighbours.next(), Some(((7u8, 5u8).into(), Disc::Empty)));
-        assert_eq!(neighbours.next(), Some(((7u8, 3u8).into(), Disc::Empty)));
-        assert_eq!(neighbours.next(), Some(((6u8, 3u8).into(), Disc::Empty)));
+        let mut neighbours = Neighbours::bottom_edge(&board, Position::at(7, 4));
+        assert_eq!(neighbours.next(), Some((Position::at(6, 4), Disc::Empty)));
+        assert_eq!(neighbours.next(), Some((Position::at(6, 5), Disc::Empty)));
+        assert_eq!(neighbours.next(), Some((Position::at(7, 5), Disc::Empty)));
+        assert_eq!(neighbours.next(), Some((Position::at(7, 3), Disc::Empty)));
+        assert_eq!(neighbours.next(), Some((Position::at(6, 3), Disc::Empty)));
         assert_eq!(neighbours.next(), None);
     }
 
     #[test]
     fn left_edge() {
         let board = Board::default();
-        let mut neighbours = Neighbours::left_edge(&board, (4u8, 0u8).into());
-        assert_eq!(neighbours.next(), Some(((3u8, 0u8).into(), Disc::Empty)));
-        assert_eq!(neighbours.next(), Some(((3u8, 1u8).into(), Disc::Empty)));
-        assert_eq!(neighbours.next(), Some(((4u8, 1u8).into(), Disc::Empty)));
-        assert_eq!(neighbours.next(), Some(((5u8, 1u8).into(), Disc::Empty)));
-        assert_eq!(neighbours.next(), Some(((5u8, 0u8).into(), Disc::Empty)));
+        let mut neighbours = Neighbours::left_edge(&board, Position::at(4, 0));
+        assert_eq!(neighbours.next(), Some((Position::at(3, 0), Disc::Empty)));
+        assert_eq!(neighbours.next(), Some((Position::at(3, 1), Disc::Empty)));
+        assert_eq!(neighbours.next(), Some((Position::at(4, 1), Disc::Empty)));
+        assert_eq!(neighbours.next(), Some((Position::at(5, 1), Disc::Empty)));
+        assert_eq!(neighbours.next(), Some((Position::at(5, 0), Disc::Empty)));
         assert_eq!(neighbours.next(), None);
     }
 
     #[test]
     fn inner_point() {
         let board = Board::default();
-        let mut neighbours = Neighbours::inner_point(&board, (3u8, 3u8).into());
-        assert_eq!(neighbours.next(), Some(((2u8, 3u8).into(), Disc::Empty)));
-        assert_eq!(neighbours.next(), Some(((2u8, 4u8).into(), Disc::Empty)));
-        assert_eq!(neighbours.next(), Some(((3u8, 4u8).into(), Disc::Player1)));
-        assert_eq!(neighbours.next(), Some(((4u8, 4u8).into(), Disc::Player2)));
-        assert_eq!(neighbours.next(), Some(((4u8, 3u8).into(), Disc::Player1)));
-        assert_eq!(neighbours.next(), Some(((4u8, 2u8).into(), Disc::Empty)));
-        assert_eq!(neighbours.next(), Some(((3u8, 2u8).into(), Disc::Empty)));
-        assert_eq!(neighbours.next(), Some(((2u8, 2u8).into(), Disc::Empty)));
+        let mut neighbours = Neighbours::inner_point(&board, Position::at(3, 3));
+        assert_eq!(neighbours.next(), Some((Position::at(2, 3), Disc::Empty)));
+        assert_eq!(neighbours.next(), Some((Position::at(2, 4), Disc::Empty)));
+        assert_eq!(neighbours.next(), Some((Position::at(3, 4), Disc::Player1)));
+        assert_eq!(neighbours.next(), Some((Position::at(4, 4), Disc::Player2)));
+        assert_eq!(neighbours.next(), Some((Position::at(4, 3), Disc::Player1)));
+        assert_eq!(neighbours.next(), Some((Position::at(4, 2), Disc::Empty)));
+        assert_eq!(neighbours.next(), Some((Position::at(3, 2), Disc::Empty)));
+        assert_eq!(neighbours.next(), Some((Position::at(2, 2), Disc::Empty)));
         assert_eq!(neighbours.next(), None);
     }
 }
@@ -0,0 +1,74 @@
+use super::{Board, Move, Player};
+use crate::error::MoveError;
+use crate::position::Position;
+use std::fmt;
+use std::hash::Hash;
+
+/// The moves of the game on one kind of board, shared by [`Board`] and every
+/// [`SizedBoard`](super::SizedBoard) so that a [`Game`](crate::Game) and
+/// [`perft`](crate::perft) can be played out on any of them.
+///
+/// The [`Default`] board is the start position.
+pub trait Rules: Clone + Default {
+    /// A square of the board
+    type Position: Copy + Eq + Hash + fmt::Debug + fmt::Display;
+    /// What a move changed, which [`Rules::unmake`] takes back
+    type Move: Copy + Eq + Hash + fmt::Debug;
+
+    /// The squares `player` can legally move to, in ascending order.
+    fn legal_moves(&self, player: Player) -> Vec<Self::Position>;
+
+    fn legal_move_count(&self, player: Player) -> usize;
+
+    /// Places a disc for `player` and flips the discs it captures, without checking that the
+    /// move is legal.
+    fn place_piece(&mut self, pos: Self::Position, player: Player) -> Self::Move;
+
+    /// Like [`Rules::place_piece`], but leaves the board untouched and returns an error if the
+    /// move is not legal for `player`.
+    fn try_place_piece(
+        &mut self,
+        pos: Self::Position,
+        player: Player,
+    ) -> Result<Self::Move, MoveError>;
+
+    /// Takes back `mv`, which must be the last move made on this board.
+    fn unmake(&mut self, mv: &Self::Move);
+
+    fn player1_count(&self) -> usize;
+
+    fn player2_count(&self) -> usize;
+}
+
+impl Rules for Board {
+    type Position = Position;
+    type Move = Move;
+
+    fn legal_moves(&self, player: Player) -> Vec<Position> {
+        self.all_legal_moves(player).collect()
+    }
+
+    fn legal_move_count(&self, player: Player) -> usize {
+        self.legal_moves_mask(player).count_ones() as usize
+    }
+
+    fn place_piece(&mut self, pos: Position, player: Player) -> Move {
+        Board::place_piece(self, pos, player)
+    }
+
+    fn try_place_piece(&mut self, pos: Position, player: Player) -> Result<Move, MoveError> {
+        Board::try_place_piece(self, pos, player)
+    }
+
+    fn unmake(&mut self, mv: &Move) {
+        Board::unmake(self, mv)
+    }
+
+    fn player1_count(&self) -> usize {
+        Board::player1_count(self)
+    }
+
+    fn player2_count(&self) -> usize {
+        Board::player2_count(self)
+    }
+}
@@ -0,0 +1,749 @@
+//! Boards of other sizes than the standard 8x8 [`Board`](super::Board), such as the solved
+//! 6x6 game or 10x10.
+//!
+//! The types mirror the standard ones: squares are [`SizedPosition`]s, written `a1` at the
+//! top left up to `j10` on 10x10, and moves are [`SizedMove`]s that [`SizedBoard::unmake`]
+//! takes back. Discs are kept in `u128` bitboards with bit `row * N + col` standing for
+//! `(row, col)`. Like [`Board`](super::Board), every size implements [`Rules`], so it can be
+//! played through a [`Game`](crate::Game).
+
+use super::{Direction, Disc, Player, Rules};
+use crate::error::MoveError;
+use crate::position::{ParsePositionError, PositionError};
+use std::convert::{TryFrom, TryInto};
+use std::fmt;
+use std::ops::Index;
+use std::str::FromStr;
+
+/// A square of a [`SizedBoard`] of `N` by `N` squares.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct SizedPosition<const N: usize> {
+    /// `row * N + col`
+    idx: u8,
+}
+
+impl<const N: usize> SizedPosition<N> {
+    /// Returns the position at `(row, col)`, or `None` if either is outside `0..N`.
+    #[allow(clippy::let_unit_value)]
+    pub const fn new(row: usize, col: usize) -> Option<Self> {
+        let () = SizedBoard::<N>::VALID;
+        if row >= N || col >= N {
+            return None;
+        }
+        Some(Self {
+            idx: (row * N + col) as u8,
+        })
+    }
+
+    /// Returns the position at `(row, col)`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if either `row` or `col` is outside `0..N`.
+    pub const fn at(row: usize, col: usize) -> Self {
+        match Self::new(row, col) {
+            Some(pos) => pos,
+            None => panic!("Index out of bounds"),
+        }
+    }
+
+    /// Returns the position with square index `idx` (`row * N + col`), or `None` if it is not
+    /// in `0..N * N`.
+    pub const fn from_index(idx: u8) -> Option<Self> {
+        let idx = idx as usize;
+        Self::new(idx / N, idx % N)
+    }
+
+    /// The square index of this position, `row * N + col`.
+    pub fn index(&self) -> usize {
+        self.idx as usize
+    }
+
+    pub fn row(&self) -> usize {
+        self.index() / N
+    }
+
+    pub fn col(&self) -> usize {
+        self.index() % N
+    }
+
+    /// The next square in `dir`, or `None` past the edge of the board.
+    fn step(self, dir: Direction) -> Option<Self> {
+        let (row, col) = (self.row(), self.col());
+        let (row, col) = match dir {
+            Direction::Up => (row.checked_sub(1)?, col),
+            Direction::UpRight => (row.checked_sub(1)?, col + 1),
+            Direction::Right => (row, col + 1),
+            Direction::DownRight => (row + 1, col + 1),
+            Direction::Down => (row + 1, col),
+            Direction::DownLeft => (row + 1, col.checked_sub(1)?),
+            Direction::Left => (row, col.checked_sub(1)?),
+            Direction::UpLeft => (row.checked_sub(1)?, col.checked_sub(1)?),
+        };
+        Self::new(row, col)
+    }
+
+    fn bit(self) -> u128 {
+        1 << self.idx
+    }
+}
+
+impl<const N: usize> fmt::Display for SizedPosition<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", (b'a' + self.col() as u8) as char, self.row() + 1)
+    }
+}
+
+impl<const N: usize> FromStr for SizedPosition<N> {
+    type Err = ParsePositionError;
+
+    /// Parses a square like `f5` or `j10`, ignoring case.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let len = s.chars().count();
+        let max_len = if N < 10 { 2 } else { 3 };
+        if len < 2 || len > max_len {
+            return Err(ParsePositionError::Length(len));
+        }
+        let mut chars = s.chars();
+        let col = chars.next().unwrap();
+        let col_idx = match col.to_ascii_lowercase() {
+            c @ 'a'..='z' if (c as usize - 'a' as usize) < N => c as usize - 'a' as usize,
+            _ => return Err(ParsePositionError::Column(col)),
+        };
+        let row = chars.as_str();
+        let first = row.chars().next().unwrap();
+        if let Some(c) = row.chars().find(|c| !c.is_ascii_digit()) {
+            return Err(ParsePositionError::Row(c));
+        }
+        let row_idx = match row.parse::<usize>() {
+            Ok(row) if first != '0' && row <= N => row - 1,
+            _ => return Err(ParsePositionError::Row(first)),
+        };
+        Ok(Self::at(row_idx, col_idx))
+    }
+}
+
+impl<const N: usize> TryFrom<(usize, usize)> for SizedPosition<N> {
+    type Error = PositionError;
+    fn try_from(p: (usize, usize)) -> Result<Self, Self::Error> {
+        Self::new(p.0, p.1).ok_or(PositionError::OutOfBounds { row: p.0, col: p.1 })
+    }
+}
+
+impl<const N: usize> TryFrom<u8> for SizedPosition<N> {
+    type Error = PositionError;
+    fn try_from(idx: u8) -> Result<Self, Self::Error> {
+        Self::from_index(idx).ok_or(PositionError::InvalidIndex(idx))
+    }
+}
+
+/// What a move changed on a [`SizedBoard`], which [`SizedBoard::unmake`] takes back.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct SizedMove<const N: usize> {
+    pos: SizedPosition<N>,
+    player: Player,
+    /// Bitboard of the flipped discs
+    flips: u128,
+}
+
+impl<const N: usize> SizedMove<N> {
+    pub fn pos(&self) -> SizedPosition<N> {
+        self.pos
+    }
+
+    pub fn player(&self) -> Player {
+        self.player
+    }
+
+    /// Bitboard of the flipped discs.
+    pub fn flips(&self) -> u128 {
+        self.flips
+    }
+
+    pub fn flip_count(&self) -> usize {
+        self.flips.count_ones() as usize
+    }
+
+    /// The flipped positions, in ascending order.
+    pub fn flipped(&self) -> impl Iterator<Item = SizedPosition<N>> {
+        squares(self.flips)
+    }
+}
+
+/// The squares of a bitboard, lowest first.
+fn squares<const N: usize>(mut mask: u128) -> impl Iterator<Item = SizedPosition<N>> {
+    std::iter::from_fn(move || {
+        if mask == 0 {
+            return None;
+        }
+        let idx = mask.trailing_zeros() as u8;
+        mask &= mask - 1;
+        Some(SizedPosition { idx })
+    })
+}
+
+/// A board of `N` by `N` squares, where `N` is even and between 4 and 10.
+///
+/// Using a board of any other size fails to compile. The board itself knows nothing of turns:
+/// play it through a [`Game`](crate::Game) to have passes and the end of the game handled.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct SizedBoard<const N: usize> {
+    p1: u128,
+    p2: u128,
+    blocked: u128,
+}
+
+pub type Board6 = SizedBoard<6>;
+pub type Board10 = SizedBoard<10>;
+
+impl<const N: usize> SizedBoard<N> {
+    const VALID: () = assert!(
+        N % 2 == 0 && N >= 4 && N <= 10,
+        "board size must be even and between 4 and 10"
+    );
+
+    /// Bitboard of every square on the board.
+    const ALL: u128 = (1 << (N * N)) - 1;
+
+    /// Bitboard of the first column.
+    const FIRST_COL: u128 = {
+        let mut bb = 0;
+        let mut row = 0;
+        while row < N {
+            bb |= 1 << (row * N);
+            row += 1;
+        }
+        bb
+    };
+
+    /// Bitboard of the last column.
+    const LAST_COL: u128 = Self::FIRST_COL << (N - 1);
+
+    #[allow(clippy::let_unit_value)]
+    pub const fn empty() -> Self {
+        let () = Self::VALID;
+        Self {
+            p1: 0,
+            p2: 0,
+            blocked: 0,
+        }
+    }
+
+    /// The number of rows and of columns.
+    pub const fn size(&self) -> usize {
+        N
+    }
+
+    pub fn get_piece(&self, pos: SizedPosition<N>) -> Disc {
+        self[pos]
+    }
+
+    /// Returns the disc at `pos`, or `None` if `pos` does not describe a square on the board.
+    pub fn get<T: TryInto<SizedPosition<N>>>(&self, pos: T) -> Option<Disc> {
+        pos.try_into().ok().map(|pos| self[pos])
+    }
+
+    pub fn set_piece(&mut self, pos: SizedPosition<N>, val: Disc) {
+        let bit = pos.bit();
+        self.p1 &= !bit;
+        self.p2 &= !bit;
+        self.blocked &= !bit;
+        match val {
+            Disc::Player1 => self.p1 |= bit,
+            Disc::Player2 => self.p2 |= bit,
+            Disc::Blocked => self.blocked |= bit,
+            Disc::Empty => (),
+        }
+    }
+
+    pub fn player1_count(&self) -> usize {
+        self.p1.count_ones() as usize
+    }
+
+    pub fn player2_count(&self) -> usize {
+        self.p2.count_ones() as usize
+    }
+
+    /// Bitboard of the discs of `player`.
+    pub fn discs(&self, player: Player) -> u128 {
+        match player {
+            Player::Player1 => self.p1,
+            Player::Player2 => self.p2,
+        }
+    }
+
+    /// Bitboard of the empty squares, leaving out blocked ones.
+    pub fn empty_squares(&self) -> u128 {
+        !(self.p1 | self.p2 | self.blocked) & Self::ALL
+    }
+
+    /// Bitboard of the blocked squares.
+    pub fn blocked_squares(&self) -> u128 {
+        self.blocked
+    }
+
+    /// The squares next to `pos`, clockwise from the one above, leaving out those off the
+    /// board.
+    pub fn neighbours(
+        &self,
+        pos: SizedPosition<N>,
+    ) -> impl Iterator<Item = (SizedPosition<N>, Disc)> + '_ {
+        Direction::ALL
+            .iter()
+            .filter_map(move |&dir| pos.step(dir))
+            .map(move |pos| (pos, self[pos]))
+    }
+
+    /// The squares from `pos` in `dir` up to the edge or a blocked square, leaving out `pos`.
+    pub fn strider(&self, pos: SizedPosition<N>, dir: Direction) -> SizedStrider<'_, N> {
+        SizedStrider {
+            board: self,
+            pos,
+            dir,
+        }
+    }
+
+    /// Bitboard of every square `player` can legally move to.
+    pub fn legal_moves_mask(&self, player: Player) -> u128 {
+        let own = self.discs(player);
+        let opp = self.discs(player.opponent());
+        Direction::ALL.iter().fold(0, |acc, &dir| {
+            let mut run = Self::shift(dir, own) & opp;
+            for _ in 0..N - 3 {
+                run |= Self::shift(dir, run) & opp;
+            }
+            acc | Self::shift(dir, run)
+        }) & self.empty_squares()
+    }
+
+    pub fn is_legal_move(&self, pos: SizedPosition<N>, player: Player) -> bool {
+        self.legal_moves_mask(player) & pos.bit() != 0
+    }
+
+    pub fn all_legal_moves(&self, player: Player) -> impl Iterator<Item = SizedPosition<N>> {
+        squares(self.legal_moves_mask(player))
+    }
+
+    /// Bitboard of the discs that a move by `player` on `pos` would flip. This is `0` if the
+    /// square is occupied or the move is illegal.
+    pub fn flips(&self, pos: SizedPosition<N>, player: Player) -> u128 {
+        let mv = pos.bit();
+        if self.empty_squares() & mv == 0 {
+            return 0;
+        }
+        let own = self.discs(player);
+        let opp = self.discs(player.opponent());
+        Direction::ALL.iter().fold(0, |acc, &dir| {
+            let mut run = Self::shift(dir, mv) & opp;
+            for _ in 0..N - 3 {
+                run |= Self::shift(dir, run) & opp;
+            }
+            if Self::shift(dir, run) & own != 0 {
+                acc | run
+            } else {
+                acc
+            }
+        })
+    }
+
+    /// Places a disc for `player` and flips the discs it captures, without checking that the
+    /// move is legal. Use [`SizedBoard::try_place_piece`] for untrusted input.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `pos` is blocked.
+    pub fn place_piece(&mut self, pos: SizedPosition<N>, player: Player) -> SizedMove<N> {
+        assert!(
+            self.blocked & pos.bit() == 0,
+            "cannot place a disc on a blocked square"
+        );
+        let flips = self.flips(pos, player);
+        self.apply(pos.bit(), player, flips);
+        SizedMove { pos, player, flips }
+    }
+
+    /// Like [`SizedBoard::place_piece`], but leaves the board untouched and returns an error
+    /// if the move is not legal for `player`.
+    pub fn try_place_piece(
+        &mut self,
+        pos: SizedPosition<N>,
+        player: Player,
+    ) -> Result<SizedMove<N>, MoveError> {
+        match self[pos] {
+            Disc::Empty => (),
+            Disc::Blocked => return Err(MoveError::Blocked),
+            _ => return Err(MoveError::Occupied),
+        }
+        let flips = self.flips(pos, player);
+        if flips == 0 {
+            return Err(MoveError::NoFlips);
+        }
+        self.apply(pos.bit(), player, flips);
+        Ok(SizedMove { pos, player, flips })
+    }
+
+    /// Takes back `mv`, which must be the last move made on this board.
+    pub fn unmake(&mut self, mv: &SizedMove<N>) {
+        let placed = mv.pos.bit();
+        debug_assert!(self.discs(mv.player) & (placed | mv.flips) == placed | mv.flips);
+        match mv.player {
+            Player::Player1 => {
+                self.p1 &= !(placed | mv.flips);
+                self.p2 |= mv.flips;
+            }
+            Player::Player2 => {
+                self.p2 &= !(placed | mv.flips);
+                self.p1 |= mv.flips;
+            }
+        }
+    }
+
+    fn apply(&mut self, placed: u128, player: Player, flips: u128) {
+        match player {
+            Player::Player1 => {
+                self.p1 |= placed | flips;
+                self.p2 &= !(placed | flips);
+            }
+            Player::Player2 => {
+                self.p2 |= placed | flips;
+                self.p1 &= !(placed | flips);
+            }
+        }
+    }
+
+    /// Moves every square of `bb` one step in `dir`, dropping squares that fall off the board.
+    fn shift(dir: Direction, bb: u128) -> u128 {
+        let shifted = match dir {
+            Direction::Up => bb >> N,
+            Direction::UpRight => (bb >> (N - 1)) & !Self::FIRST_COL,
+            Direction::Right => (bb << 1) & !Self::FIRST_COL,
+            Direction::DownRight => (bb << (N + 1)) & !Self::FIRST_COL,
+            Direction::Down => bb << N,
+            Direction::DownLeft => (bb << (N - 1)) & !Self::LAST_COL,
+            Direction::Left => (bb >> 1) & !Self::LAST_COL,
+            Direction::UpLeft => (bb >> (N + 1)) & !Self::LAST_COL,
+        };
+        shifted & Self::ALL
+    }
+}
+
+impl<const N: usize> Default for SizedBoard<N> {
+    /// The start position, with the four middle squares filled like on the 8x8 board.
+    fn default() -> Self {
+        let mut board = Self::empty();
+        let mid = N / 2;
+        board.set_piece(SizedPosition::at(mid - 1, mid - 1), Disc::Player2);
+        board.set_piece(SizedPosition::at(mid - 1, mid), Disc::Player1);
+        board.set_piece(SizedPosition::at(mid, mid - 1), Disc::Player1);
+        board.set_piece(SizedPosition::at(mid, mid), Disc::Player2);
+        board
+    }
+}
+
+impl<const N: usize> Index<SizedPosition<N>> for SizedBoard<N> {
+    type Output = Disc;
+    fn index(&self, pos: SizedPosition<N>) -> &Self::Output {
+        let bit = pos.bit();
+        if self.blocked & bit != 0 {
+            return &Disc::Blocked;
+        }
+        match (self.p1 & bit != 0, self.p2 & bit != 0) {
+            (false, false) => &Disc::Empty,
+            (false, true) => &Disc::Player2,
+            (true, false) => &Disc::Player1,
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl<const N: usize> Rules for SizedBoard<N> {
+    type Position = SizedPosition<N>;
+    type Move = SizedMove<N>;
+
+    fn legal_moves(&self, player: Player) -> Vec<SizedPosition<N>> {
+        self.all_legal_moves(player).collect()
+    }
+
+    fn legal_move_count(&self, player: Player) -> usize {
+        self.legal_moves_mask(player).count_ones() as usize
+    }
+
+    fn place_piece(&mut self, pos: SizedPosition<N>, player: Player) -> SizedMove<N> {
+        SizedBoard::place_piece(self, pos, player)
+    }
+
+    fn try_place_piece(
+        &mut self,
+        pos: SizedPosition<N>,
+        player: Player,
+    ) -> Result<SizedMove<N>, MoveError> {
+        SizedBoard::try_place_piece(self, pos, player)
+    }
+
+    fn unmake(&mut self, mv: &SizedMove<N>) {
+        SizedBoard::unmake(self, mv)
+    }
+
+    fn player1_count(&self) -> usize {
+        SizedBoard::player1_count(self)
+    }
+
+    fn player2_count(&self) -> usize {
+        SizedBoard::player2_count(self)
+    }
+}
+
+/// The squares of a [`SizedBoard`] in one direction, up to the edge or a blocked square.
+#[derive(Debug)]
+pub struct SizedStrider<'a, const N: usize> {
+    board: &'a SizedBoard<N>,
+    pos: SizedPosition<N>,
+    dir: Direction,
+}
+
+impl<'a, const N: usize> SizedStrider<'a, N> {
+    pub fn board(&self) -> &'a SizedBoard<N> {
+        self.board
+    }
+
+    /// The last square visited, or the starting square before the first call to `next`.
+    pub fn pos(&self) -> SizedPosition<N> {
+        self.pos
+    }
+
+    pub fn dir(&self) -> Direction {
+        self.dir
+    }
+}
+
+impl<'a, const N: usize> Iterator for SizedStrider<'a, N> {
+    type Item = (SizedPosition<N>, Disc);
+    fn next(&mut self) -> Option<Self::Item> {
+        let pos = self.pos.step(self.dir)?;
+        if self.board[pos] == Disc::Blocked {
+            return None;
+        }
+        self.pos = pos;
+        Some((pos, self.board[pos]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::Board;
+    use crate::game::{Game, Ply, Status};
+    use crate::perft::perft;
+    use crate::position::Position;
+    use crate::rng::Rng;
+
+    fn sq<const N: usize>(s: &str) -> SizedPosition<N> {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_positions() {
+        let pos = SizedPosition::<10>::at(9, 2);
+        assert_eq!((pos.row(), pos.col(), pos.index()), (9, 2, 92));
+        assert_eq!(pos.to_string(), "c10");
+        assert_eq!(sq::<10>("C10"), pos);
+        assert_eq!(SizedPosition::<10>::from_index(92), Some(pos));
+        assert_eq!(SizedPosition::<10>::from_index(100), None);
+        assert_eq!(SizedPosition::<6>::new(6, 0), None);
+        assert_eq!(
+            SizedPosition::<6>::try_from((2, 6)),
+            Err(PositionError::OutOfBounds { row: 2, col: 6 })
+        );
+        assert_eq!(sq::<6>("f6"), SizedPosition::at(5, 5));
+        assert_eq!(
+            "g1".parse::<SizedPosition<6>>(),
+            Err(ParsePositionError::Column('g'))
+        );
+        assert_eq!(
+            "a7".parse::<SizedPosition<6>>(),
+            Err(ParsePositionError::Row('7'))
+        );
+        assert_eq!(
+            "a10".parse::<SizedPosition<6>>(),
+            Err(ParsePositionError::Length(3))
+        );
+        assert_eq!(
+            "a11".parse::<SizedPosition<10>>(),
+            Err(ParsePositionError::Row('1'))
+        );
+        assert_eq!(
+            "a0".parse::<SizedPosition<10>>(),
+            Err(ParsePositionError::Row('0'))
+        );
+        assert_eq!(
+            "a+1".parse::<SizedPosition<10>>(),
+            Err(ParsePositionError::Row('+'))
+        );
+    }
+
+    #[test]
+    fn test_start_position() {
+        let board = Board6::default();
+        assert_eq!(board.size(), 6);
+        assert_eq!(board[sq("c3")], Disc::Player2);
+        assert_eq!(board[sq("d3")], Disc::Player1);
+        assert_eq!(board[sq("c4")], Disc::Player1);
+        assert_eq!(board[sq("d4")], Disc::Player2);
+        assert_eq!(
+            board.all_legal_moves(Player::Player1).collect::<Vec<_>>(),
+            vec![sq("c2"), sq("b3"), sq("e4"), sq("d5")]
+        );
+
+        let board = Board10::default();
+        assert_eq!(board[sq("e5")], Disc::Player2);
+        assert_eq!(board[sq("f6")], Disc::Player2);
+        assert_eq!(
+            board.all_legal_moves(Player::Player1).collect::<Vec<_>>(),
+            vec![sq("e4"), sq("d5"), sq("g6"), sq("f7")]
+        );
+        assert_eq!(board.get((9, 9)), Some(Disc::Empty));
+        assert_eq!(board.get((10, 0)), None);
+        assert_eq!(board.get(100), None);
+    }
+
+    #[test]
+    fn test_neighbours_and_strider() {
+        let board = Board6::default();
+        let corner: Vec<_> = board.neighbours(sq("f6")).map(|(pos, _)| pos).collect();
+        assert_eq!(corner, vec![sq("f5"), sq("e6"), sq("e5")]);
+        assert_eq!(board.neighbours(sq("d1")).count(), 5);
+        assert_eq!(board.neighbours(sq("c3")).count(), 8);
+
+        let line: Vec<_> = board.strider(sq("b2"), Direction::DownRight).collect();
+        assert_eq!(
+            line,
+            vec![
+                (sq("c3"), Disc::Player2),
+                (sq("d4"), Disc::Player2),
+                (sq("e5"), Disc::Empty),
+                (sq("f6"), Disc::Empty),
+            ]
+        );
+        assert_eq!(board.strider(sq("f1"), Direction::UpRight).next(), None);
+
+        let mut board = board;
+        board.set_piece(sq("e5"), Disc::Blocked);
+        let mut strider = board.strider(sq("b2"), Direction::DownRight);
+        assert_eq!(strider.by_ref().count(), 2);
+        assert_eq!(strider.pos(), sq("d4"));
+        assert!(board
+            .neighbours(sq("f6"))
+            .any(|n| n == (sq("e5"), Disc::Blocked)));
+        assert_eq!(
+            board.try_place_piece(sq("e5"), Player::Player1),
+            Err(MoveError::Blocked)
+        );
+        assert_eq!(board.empty_squares().count_ones(), 31);
+    }
+
+    #[test]
+    fn test_place_piece() {
+        let mut board = Board10::default();
+        assert_eq!(
+            board.try_place_piece(sq("a1"), Player::Player1),
+            Err(MoveError::NoFlips)
+        );
+        assert_eq!(
+            board.try_place_piece(sq("e5"), Player::Player1),
+            Err(MoveError::Occupied)
+        );
+        assert_eq!(board.flips(sq("j10"), Player::Player1), 0);
+        assert_eq!(board, Board10::default());
+        let mv = board.try_place_piece(sq("e4"), Player::Player1).unwrap();
+        assert_eq!(mv.pos(), sq("e4"));
+        assert_eq!(mv.player(), Player::Player1);
+        assert_eq!(mv.flips(), 1 << 44);
+        assert_eq!(mv.flipped().collect::<Vec<_>>(), vec![sq("e5")]);
+        assert_eq!((board.player1_count(), board.player2_count()), (4, 1));
+        board.unmake(&mv);
+        assert_eq!(board, Board10::default());
+    }
+
+    #[test]
+    fn test_edges_do_not_wrap() {
+        // A run along the bottom row must not carry on into the next row or off the board
+        let mut board = SizedBoard::<4>::empty();
+        board.set_piece(sq("d1"), Disc::Player2);
+        board.set_piece(sq("a2"), Disc::Player1);
+        assert_eq!(board.legal_moves_mask(Player::Player1), 0);
+        board.set_piece(sq("b4"), Disc::Player2);
+        board.set_piece(sq("c4"), Disc::Player1);
+        assert_eq!(board.legal_moves_mask(Player::Player1), 1 << 12);
+    }
+
+    #[test]
+    fn test_matches_board() {
+        // On 8x8 the sized board must play exactly like the standard one
+        let mut rng = Rng::new(17);
+        for _ in 0..20 {
+            let mut board = Board::default();
+            let mut sized = SizedBoard::<8>::default();
+            let mut player = Player::Player1;
+            loop {
+                let moves = board.legal_moves_mask(player);
+                assert_eq!(sized.legal_moves_mask(player), moves as u128);
+                if moves == 0 {
+                    player = player.opponent();
+                    if board.legal_moves_mask(player) == 0 {
+                        break;
+                    }
+                    continue;
+                }
+                let pos = Position {
+                    idx: rng.pick_bit(moves) as u8,
+                };
+                let flips = board.place_piece(pos, player).flips();
+                let sized_pos = SizedPosition::at(pos.row(), pos.col());
+                assert_eq!(sized_pos.to_string(), pos.to_string());
+                assert_eq!(sized.place_piece(sized_pos, player).flips(), flips as u128);
+                player = player.opponent();
+            }
+            assert_eq!(sized.player1_count(), board.player1_count());
+            assert_eq!(sized.player2_count(), board.player2_count());
+        }
+
+        for depth in 0..=5 {
+            assert_eq!(
+                perft(&SizedBoard::<8>::default(), Player::Player1, depth),
+                perft(&Board::default(), Player::Player1, depth)
+            );
+        }
+    }
+
+    #[test]
+    fn test_game() {
+        let mut game = Game::from_board(Board6::default(), Player::Player1);
+        assert_eq!(game.legal_moves().count(), 4);
+        let mv = game.play(sq("c2")).unwrap();
+        assert_eq!(mv.flipped().collect::<Vec<_>>(), vec![sq("c3")]);
+        assert_eq!(game.play(sq("c2")), Err(MoveError::Occupied));
+        assert_eq!(game.undo(), Some(mv));
+        assert_eq!(game.board(), &Board6::default());
+
+        // Play the game out, always taking the last move
+        while !game.is_over() {
+            let pos = game.legal_moves().last().unwrap();
+            game.play(pos).unwrap();
+        }
+        let moves = game
+            .history()
+            .iter()
+            .filter(|ply| matches!(ply, Ply::Move(..)))
+            .count();
+        assert!(moves <= 32);
+        match game.status() {
+            Status::Finished { score, .. } => {
+                assert_eq!(
+                    score,
+                    (game.board().player1_count(), game.board().player2_count())
+                )
+            }
+            status => panic!("unexpected {:?}", status),
+        }
+    }
+}
@@ -1,4 +1,4 @@
-#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
 pub enum Direction {
     Up,
     UpRight,
@@ -13,16 +13,34 @@ pub enum Direction {
 use crate::board::{Board, Disc};
 use crate::position::Position;
 
+/// The squares from a position in one direction, up to the edge or a blocked square.
 #[derive(Debug)]
 pub struct Strider<'a> {
-    pub board: &'a Board,
-    pub pos: Position,
-    pub dir: Direction,
+    pub(super) board: &'a Board,
+    pub(super) pos: Position,
+    pub(super) dir: Direction,
+}
+
+impl<'a> Strider<'a> {
+    pub fn board(&self) -> &'a Board {
+        self.board
+    }
+
+    /// The last square visited, or the starting square before the first call to `next`.
+    pub fn pos(&self) -> Position {
+        self.pos
+    }
+
+    pub fn dir(&self) -> Direction {
+        self.dir
+    }
 }
 
 impl<'a> Iterator for Strider<'a> {
     type Item = (Position, Disc);
+    /// Stops at the edge of the board or at a blocked square.
     fn next(&mut self) -> Option<Self::Item> {
+        let from = self.pos;
         match self.dir {
             Direction::Up => {
                 if self.pos.row() == 0 {
@@ -77,6 +95,10 @@ impl<'a> Iterator for Strider<'a> {
                 self.pos.idx -= 1 << 3;
             }
         }
+        if self.board[self.pos] == Disc::Blocked {
+            self.pos = from;
+            return None;
+        }
         Some((self.pos, self.board[self.pos]))
     }
 }
@@ -95,9 +117,9 @@ mod tests {
             pos: POS,
             dir: Direction::Up,
         };
-        assert_eq!(strider.next(), Some(((2u8, 4u8).into(), Disc::Empty)));
-        assert_eq!(strider.next(), Some(((1u8, 4u8).into(), Disc::Empty)));
-        assert_eq!(strider.next(), Some(((0u8, 4u8).into(), Disc::Empty)));
+        assert_eq!(strider.next(), Some((Position::at(2, 4), Disc::Empty)));
+        assert_eq!(strider.next(), Some((Position::at(1, 4), Disc::Empty)));
+        assert_eq!(strider.next(), Some((Position::at(0, 4), Disc::Empty)));
         assert_eq!(strider.next(), None);
     }
 
@@ -108,9 +130,9 @@ mod tests {
             pos: POS,
             dir: Direction::UpRight,
         };
-        assert_eq!(strider.next(), Some(((2u8, 5u8).into(), Disc::Empty)));
-        assert_eq!(strider.next(), Some(((1u8, 6u8).into(), Disc::Empty)));
-        assert_eq!(strider.next(), Some(((0u8, 7u8).into(), Disc::Empty)));
+        assert_eq!(strider.next(), Some((Position::at(2, 5), Disc::Empty)));
+        assert_eq!(strider.next(), Some((Position::at(1, 6), Disc::Empty)));
+        assert_eq!(strider.next(), Some((Position::at(0, 7), Disc::Empty)));
         assert_eq!(strider.next(), None);
     }
 
@@ -121,9 +143,9 @@ mod tests {
             pos: POS,
             dir: Direction::Right,
         };
-        assert_eq!(strider.next(), Some(((3u8, 5u8).into(), Disc::Empty)));
-        assert_eq!(strider.next(), Some(((3u8, 6u8).into(), Disc::Empty)));
-        assert_eq!(strider.next(), Some(((3u8, 7u8).into(), Disc::Empty)));
+        assert_eq!(strider.next(), Some((Position::at(3, 5), Disc::Empty)));
+        assert_eq!(strider.next(), Some((Position::at(3, 6), Disc::Empty)));
+        assert_eq!(strider.next(), Some((Position::at(3, 7), Disc::Empty)));
         assert_eq!(strider.next(), None);
     }
 
@@ -134,9 +156,9 @@ mod tests {
             pos: POS,
             dir: Direction::DownRight,
         };
-        assert_eq!(strider.next(), Some(((4u8, 5u8).into(), Disc::Empty)));
-        assert_eq!(strider.next(), Some(((5u8, 6u8).into(), Disc::Empty)));
-        assert_eq!(strider.next(), Some(((6u8, 7u8).into(), Disc::Empty)));
+        assert_eq!(strider.next(), Some((Position::at(4, 5), Disc::Empty)));
+        assert_eq!(strider.next(), Some((Position::at(5, 6), Disc::Empty)));
+        assert_eq!(strider.next(), Some((Position::at(6, 7), Disc::Empty)));
         assert_eq!(strider.next(), None);
     }
 
@@ -147,10 +169,10 @@ mod tests {
             pos: POS,
             dir: Direction::Down,
         };
-        assert_eq!(strider.next(), Some(((4u8, 4u8).into(), Disc::Empty)));
-        assert_eq!(strider.next(), Some(((5u8, 4u8).into(), Disc::Empty)));
-        assert_eq!(strider.next(), Some(((6u8, 4u8).into(), Disc::Empty)));
-        assert_eq!(strider.next(), Some(((7u8, 4u8).into(), Disc::Empty)));
+        assert_eq!(strider.next(), Some((Position::at(4, 4), Disc::Empty)));
+        assert_eq!(strider.next(), Some((Position::at(5, 4), Disc::Empty)));
+        assert_eq!(strider.next(), Some((Position::at(6, 4), Disc::Empty)));
+        assert_eq!(strider.next(), Some((Position::at(7, 4), Disc::Empty)));
         assert_eq!(strider.next(), None);
     }
 
@@ -161,10 +183,10 @@ mod tests {
             pos: POS,
             dir: Direction::DownLeft,
         };
-        assert_eq!(strider.next(), Some(((4u8, 3u8).into(), Disc::Empty)));
-        assert_eq!(strider.next(), Some(((5u8, 2u8).into(), Disc::Empty)));
-        assert_eq!(strider.next(), Some(((6u8, 1u8).into(), Disc::Empty)));
-        assert_eq!(strider.next(), Some(((7u8, 0u8).into(), Disc::Empty)));
+        assert_eq!(strider.next(), Some((Position::at(4, 3), Disc::Empty)));
+        assert_eq!(strider.next(), Some((Position::at(5, 2), Disc::Empty)));
+        assert_eq!(strider.next(), Some((Position::at(6, 1), Disc::Empty)));
+        assert_eq!(strider.next(), Some((Position::at(7, 0), Disc::Empty)));
         assert_eq!(strider.next(), None);
     }
 
@@ -175,10 +197,10 @@ mod tests {
             pos: POS,
             dir: Direction::Left,
         };
-        assert_eq!(strider.next(), Some(((3u8, 3u8).into(), Disc::Empty)));
-        assert_eq!(strider.next(), Some(((3u8, 2u8).into(), Disc::Empty)));
-        assert_eq!(strider.next(), Some(((3u8, 1u8).into(), Disc::Empty)));
-        assert_eq!(strider.next(), Some(((3u8, 0u8).into(), Disc::Empty)));
+        assert_eq!(strider.next(), Some((Position::at(3, 3), Disc::Empty)));
+        assert_eq!(strider.next(), Some((Position::at(3, 2), Disc::Empty)));
+        assert_eq!(strider.next(), Some((Position::at(3, 1), Disc::Empty)));
+        assert_eq!(strider.next(), Some((Position::at(3, 0), Disc::Empty)));
         assert_eq!(strider.next(), None);
     }
 
@@ -189,9 +211,9 @@ mod tests {
             pos: POS,
             dir: Direction::UpLeft,
         };
-        assert_eq!(strider.next(), Some(((2u8, 3u8).into(), Disc::Empty)));
-        assert_eq!(strider.next(), Some(((1u8, 2u8).into(), Disc::Empty)));
-        assert_eq!(strider.next(), Some(((0u8, 1u8).into(), Disc::Empty)));
+        assert_eq!(strider.next(), Some((Position::at(2, 3), Disc::Empty)));
+        assert_eq!(strider.next(), Some((Position::at(1, 2), Disc::Empty)));
+        assert_eq!(strider.next(), Some((Position::at(0, 1), Disc::Empty)));
         assert_eq!(strider.next(), None);
     }
 }
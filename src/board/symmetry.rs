@@ -0,0 +1,144 @@
+//! The eight symmetries of the board (rotations and reflections).
+
+/// Swaps rows: `(row, col)` to `(7 - row, col)`
+fn flip_vertical(bb: u64) -> u64 {
+    bb.swap_bytes()
+}
+
+/// Swaps columns: `(row, col)` to `(row, 7 - col)`
+fn mirror_horizontal(mut bb: u64) -> u64 {
+    const K1: u64 = 0x5555_5555_5555_5555;
+    const K2: u64 = 0x3333_3333_3333_3333;
+    const K4: u64 = 0x0f0f_0f0f_0f0f_0f0f;
+    bb = ((bb >> 1) & K1) | ((bb & K1) << 1);
+    bb = ((bb >> 2) & K2) | ((bb & K2) << 2);
+    ((bb >> 4) & K4) | ((bb & K4) << 4)
+}
+
+/// Transposes: `(row, col)` to `(col, row)`
+fn flip_diagonal(mut bb: u64) -> u64 {
+    const K1: u64 = 0x5500_5500_5500_5500;
+    const K2: u64 = 0x3333_0000_3333_0000;
+    const K4: u64 = 0x0f0f_0f0f_0000_0000;
+    let mut t = K4 & (bb ^ (bb << 28));
+    bb ^= t ^ (t >> 28);
+    t = K2 & (bb ^ (bb << 14));
+    bb ^= t ^ (t >> 14);
+    t = K1 & (bb ^ (bb << 7));
+    bb ^= t ^ (t >> 7);
+    bb
+}
+
+/// Transposes along the other diagonal: `(row, col)` to `(7 - col, 7 - row)`
+fn flip_anti_diagonal(mut bb: u64) -> u64 {
+    const K1: u64 = 0xaa00_aa00_aa00_aa00;
+    const K2: u64 = 0xcccc_0000_cccc_0000;
+    const K4: u64 = 0xf0f0_f0f0_0f0f_0f0f;
+    let mut t = bb ^ (bb << 36);
+    bb ^= K4 & (t ^ (bb >> 36));
+    t = K2 & (bb ^ (bb << 18));
+    bb ^= t ^ (t >> 18);
+    t = K1 & (bb ^ (bb << 9));
+    bb ^= t ^ (t >> 9);
+    bb
+}
+
+/// One of the eight ways to rotate or reflect the board onto itself. Rows are drawn with
+/// row 0 (`a1`-`h1`) at the top, as in the [`Display`](std::fmt::Display) of a board.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Symmetry {
+    Identity,
+    /// Quarter turn clockwise
+    Rotate90,
+    Rotate180,
+    /// Quarter turn anticlockwise
+    Rotate270,
+    /// Reflection swapping the top and bottom rows
+    FlipVertical,
+    /// Reflection swapping the left and right columns
+    FlipHorizontal,
+    /// Reflection along the a1-h8 diagonal
+    FlipDiagonal,
+    /// Reflection along the h1-a8 diagonal
+    FlipAntiDiagonal,
+}
+
+impl Symmetry {
+    pub const ALL: [Symmetry; 8] = [
+        Symmetry::Identity,
+        Symmetry::Rotate90,
+        Symmetry::Rotate180,
+        Symmetry::Rotate270,
+        Symmetry::FlipVertical,
+        Symmetry::FlipHorizontal,
+        Symmetry::FlipDiagonal,
+        Symmetry::FlipAntiDiagonal,
+    ];
+
+    /// The symmetry undoing this one.
+    pub fn inverse(self) -> Self {
+        match self {
+            Symmetry::Rotate90 => Symmetry::Rotate270,
+            Symmetry::Rotate270 => Symmetry::Rotate90,
+            other => other,
+        }
+    }
+
+    /// Moves every bit of `bb` to the square this symmetry takes it to.
+    pub fn apply_bitboard(self, bb: u64) -> u64 {
+        match self {
+            Symmetry::Identity => bb,
+            Symmetry::Rotate90 => mirror_horizontal(flip_diagonal(bb)),
+            Symmetry::Rotate180 => flip_vertical(mirror_horizontal(bb)),
+            Symmetry::Rotate270 => flip_vertical(flip_diagonal(bb)),
+            Symmetry::FlipVertical => flip_vertical(bb),
+            Symmetry::FlipHorizontal => mirror_horizontal(bb),
+            Symmetry::FlipDiagonal => flip_diagonal(bb),
+            Symmetry::FlipAntiDiagonal => flip_anti_diagonal(bb),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::position::Position;
+
+    #[test]
+    fn test_bitboards_match_positions() {
+        for &sym in &Symmetry::ALL {
+            for idx in 0..64 {
+                let pos = Position::from_index(idx).unwrap();
+                let moved = pos.transform(sym);
+                assert_eq!(
+                    sym.apply_bitboard(1 << idx),
+                    1 << moved.index(),
+                    "{:?} of {:?}",
+                    sym,
+                    pos
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_inverse() {
+        let bb = 0x0123_4567_89ab_cdef;
+        for &sym in &Symmetry::ALL {
+            assert_eq!(sym.inverse().apply_bitboard(sym.apply_bitboard(bb)), bb);
+        }
+    }
+
+    #[test]
+    fn test_rotation() {
+        // a1 goes to h1 on a clockwise quarter turn when row 0 is drawn at the top
+        assert_eq!(
+            Position::at(0, 0).transform(Symmetry::Rotate90),
+            Position::at(0, 7)
+        );
+        assert_eq!(
+            Position::at(0, 7).transform(Symmetry::Rotate90),
+            Position::at(7, 7)
+        );
+    }
+}
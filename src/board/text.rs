@@ -0,0 +1,373 @@
+//! Text forms of a board: a grid for people and a one-line string for tools.
+//!
+//! The one-line form lists the 64 squares from `a1` to `h8`, row by row, with `X` for player
+//! 1 (black), `O` for player 2 (white), `-` for an empty square and `#` for a blocked one,
+//! followed by a space and the side to move. The start position reads
+//!
+//! ```text
+//! ---------------------------OX------XO--------------------------- X
+//! ```
+//!
+//! A [`SizedBoard`] of `N` by `N` squares is written the same way with `N * N` squares.
+
+use super::{Board, Disc, Player, SizedBoard, SizedPosition};
+use crate::position::Position;
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ParseBoardError {
+    /// The string does not start with the squares of the board
+    Length { expected: usize, found: usize },
+    /// Square `index` holds a character that is not a disc or an empty square
+    Square { index: usize, found: char },
+    /// The squares are not followed by the side to move
+    MissingSideToMove,
+    /// The side to move is neither `X` nor `O`, or is followed by more text
+    SideToMove(char),
+}
+
+impl fmt::Display for ParseBoardError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseBoardError::Length { expected, found } => {
+                write!(f, "expected {} squares, got {}", expected, found)
+            }
+            ParseBoardError::Square { index, found } => {
+                write!(f, "invalid square {:?} at index {}", found, index)
+            }
+            ParseBoardError::MissingSideToMove => write!(f, "missing side to move"),
+            ParseBoardError::SideToMove(c) => write!(f, "invalid side to move {:?}", c),
+        }
+    }
+}
+
+impl Error for ParseBoardError {}
+
+fn disc_char(disc: Disc) -> char {
+    match disc {
+        Disc::Player1 => 'X',
+        Disc::Player2 => 'O',
+        Disc::Empty => '-',
+        Disc::Blocked => '#',
+    }
+}
+
+fn player_char(player: Player) -> char {
+    disc_char(player.into())
+}
+
+/// Reads a square, also accepting the spellings of other common tools.
+fn parse_disc(c: char) -> Option<Disc> {
+    match c {
+        'X' | 'x' | 'B' | 'b' | '*' => Some(Disc::Player1),
+        'O' | 'o' | 'W' | 'w' => Some(Disc::Player2),
+        '-' | '.' | '_' => Some(Disc::Empty),
+        '#' => Some(Disc::Blocked),
+        _ => None,
+    }
+}
+
+/// Splits off and parses `count` squares, returning them and the rest of the string.
+fn parse_squares(s: &str, count: usize) -> Result<(Vec<Disc>, &str), ParseBoardError> {
+    let s = s.trim();
+    let mut squares = Vec::with_capacity(count);
+    let mut chars = s.chars();
+    for index in 0..count {
+        let c = match chars.next() {
+            Some(c) if !c.is_whitespace() => c,
+            _ => {
+                return Err(ParseBoardError::Length {
+                    expected: count,
+                    found: index,
+                })
+            }
+        };
+        squares.push(parse_disc(c).ok_or(ParseBoardError::Square { index, found: c })?);
+    }
+    let rest = chars.as_str();
+    if rest.starts_with(|c: char| !c.is_whitespace()) {
+        return Err(ParseBoardError::Length {
+            expected: count,
+            found: count + rest.split_whitespace().next().unwrap().chars().count(),
+        });
+    }
+    Ok((squares, rest.trim_start()))
+}
+
+/// Parses the side to move, which must be all that is left after the squares.
+fn parse_side_to_move(rest: &str) -> Result<Player, ParseBoardError> {
+    let mut rest = rest.chars();
+    match (rest.next(), rest.next()) {
+        (None, _) => Err(ParseBoardError::MissingSideToMove),
+        (Some(c), None) => match parse_disc(c) {
+            Some(Disc::Player1) => Ok(Player::Player1),
+            Some(Disc::Player2) => Ok(Player::Player2),
+            _ => Err(ParseBoardError::SideToMove(c)),
+        },
+        (Some(c), Some(_)) => Err(ParseBoardError::SideToMove(c)),
+    }
+}
+
+fn board_from_squares(squares: &[Disc]) -> Board {
+    let mut board = Board::empty();
+    for (idx, &disc) in squares.iter().enumerate() {
+        if disc != Disc::Empty {
+            board.set_piece(Position { idx: idx as u8 }, disc);
+        }
+    }
+    board
+}
+
+fn sized_board_from_squares<const N: usize>(squares: &[Disc]) -> SizedBoard<N> {
+    let mut board = SizedBoard::empty();
+    for (idx, &disc) in squares.iter().enumerate() {
+        if disc != Disc::Empty {
+            board.set_piece(SizedPosition::at(idx / N, idx % N), disc);
+        }
+    }
+    board
+}
+
+impl Board {
+    /// The one-line form of the board with `to_move` to play.
+    pub fn to_text(&self, to_move: Player) -> String {
+        let mut text: String = self.iter().map(disc_char).collect();
+        text.push(' ');
+        text.push(player_char(to_move));
+        text
+    }
+
+    /// Parses the one-line form of a board and the side to move.
+    pub fn from_text(s: &str) -> Result<(Board, Player), ParseBoardError> {
+        let (squares, rest) = parse_squares(s, 64)?;
+        let player = parse_side_to_move(rest)?;
+        Ok((board_from_squares(&squares), player))
+    }
+}
+
+impl FromStr for Board {
+    type Err = ParseBoardError;
+
+    /// Parses the one-line form of a board. The side to move may be left out, and is ignored.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (squares, rest) = parse_squares(s, 64)?;
+        if !rest.is_empty() {
+            parse_side_to_move(rest)?;
+        }
+        Ok(board_from_squares(&squares))
+    }
+}
+
+impl fmt::Display for Board {
+    /// Draws the board as a grid with columns `a` to `h` across and rows `1` to `8` down.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "  a b c d e f g h")?;
+        for row in 0..8 {
+            write!(f, "{}", row + 1)?;
+            for col in 0..8 {
+                write!(f, " {}", disc_char(self[Position::at(row, col)]))?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+impl<const N: usize> SizedBoard<N> {
+    /// The one-line form of the board with `to_move` to play.
+    pub fn to_text(&self, to_move: Player) -> String {
+        let mut text: String = (0..N * N)
+            .map(|idx| disc_char(self[SizedPosition::at(idx / N, idx % N)]))
+            .collect();
+        text.push(' ');
+        text.push(player_char(to_move));
+        text
+    }
+
+    /// Parses the one-line form of a board and the side to move.
+    pub fn from_text(s: &str) -> Result<(Self, Player), ParseBoardError> {
+        let (squares, rest) = parse_squares(s, N * N)?;
+        let player = parse_side_to_move(rest)?;
+        Ok((sized_board_from_squares(&squares), player))
+    }
+}
+
+impl<const N: usize> FromStr for SizedBoard<N> {
+    type Err = ParseBoardError;
+
+    /// Parses the one-line form of a board. The side to move may be left out, and is ignored.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (squares, rest) = parse_squares(s, N * N)?;
+        if !rest.is_empty() {
+            parse_side_to_move(rest)?;
+        }
+        Ok(sized_board_from_squares(&squares))
+    }
+}
+
+impl<const N: usize> fmt::Display for SizedBoard<N> {
+    /// Draws the board as a grid with lettered columns across and numbered rows down.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "  ")?;
+        for col in 0..N {
+            write!(f, " {}", (b'a' + col as u8) as char)?;
+        }
+        writeln!(f)?;
+        for row in 0..N {
+            write!(f, "{:2}", row + 1)?;
+            for col in 0..N {
+                write!(f, " {}", disc_char(self[SizedPosition::at(row, col)]))?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::{Board10, Board6};
+
+    const START: &str = "---------------------------OX------XO--------------------------- X";
+
+    #[test]
+    fn test_to_text() {
+        assert_eq!(Board::default().to_text(Player::Player1), START);
+        let mut board = Board::default();
+        board.place_piece(Position::at(4, 5), Player::Player1);
+        assert_eq!(
+            board.to_text(Player::Player2),
+            "---------------------------OX------XXX-------------------------- O"
+        );
+    }
+
+    #[test]
+    fn test_round_trip() {
+        assert_eq!(
+            Board::from_text(START),
+            Ok((Board::default(), Player::Player1))
+        );
+        let mut board = Board::default();
+        let mut player = Player::Player1;
+        for _ in 0..40 {
+            // A side without moves passes
+            if let Some(pos) = board.all_legal_moves(player).last() {
+                board.place_piece(pos, player);
+            }
+            player = player.opponent();
+            assert_eq!(
+                Board::from_text(&board.to_text(player)),
+                Ok((board.clone(), player))
+            );
+        }
+    }
+
+    #[test]
+    fn test_blocked_round_trip() {
+        let text = START.replacen('-', "#", 2).replacen('-', "#", 1);
+        let (board, player) = Board::from_text(&text).unwrap();
+        assert_eq!(board.blocked_squares(), 0b111);
+        assert_eq!(board[Position::at(0, 1)], Disc::Blocked);
+        assert_eq!(board.to_text(player), text);
+        assert!(board
+            .to_string()
+            .starts_with("  a b c d e f g h\n1 # # # -"));
+    }
+
+    #[test]
+    fn test_from_str() {
+        let squares = &START[..64];
+        assert_eq!(squares.parse(), Ok(Board::default()));
+        assert_eq!(START.parse(), Ok(Board::default()));
+        let other_tools = squares.replace('-', ".").replace('X', "*");
+        assert_eq!(
+            Board::from_text(&format!("{}\tw", other_tools.to_lowercase())),
+            Ok((Board::default(), Player::Player2))
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            Board::from_text(&START[..60]),
+            Err(ParseBoardError::Length {
+                expected: 64,
+                found: 60
+            })
+        );
+        assert_eq!(
+            Board::from_text(&format!("-{}", START)),
+            Err(ParseBoardError::Length {
+                expected: 64,
+                found: 65
+            })
+        );
+        assert_eq!(
+            Board::from_text(&START.replace(" X", "éé X")),
+            Err(ParseBoardError::Length {
+                expected: 64,
+                found: 66
+            })
+        );
+        assert_eq!(
+            Board::from_text(&START.replacen('O', "Q", 1)),
+            Err(ParseBoardError::Square {
+                index: 27,
+                found: 'Q'
+            })
+        );
+        assert_eq!(
+            Board::from_text(&START[..64]),
+            Err(ParseBoardError::MissingSideToMove)
+        );
+        assert_eq!(
+            Board::from_text(&START.replace(" X", " -")),
+            Err(ParseBoardError::SideToMove('-'))
+        );
+        assert_eq!(
+            "-- X".parse::<Board>(),
+            Err(ParseBoardError::Length {
+                expected: 64,
+                found: 2
+            })
+        );
+    }
+
+    #[test]
+    fn test_display() {
+        let expected = "  a b c d e f g h
+1 - - - - - - - -
+2 - - - - - - - -
+3 - - - - - - - -
+4 - - - O X - - -
+5 - - - X O - - -
+6 - - - - - - - -
+7 - - - - - - - -
+8 - - - - - - - -
+";
+        assert_eq!(Board::default().to_string(), expected);
+    }
+
+    #[test]
+    fn test_sized_boards() {
+        let start = "--------------OX----XO-------------- X";
+        let board = Board6::default();
+        assert_eq!(board.to_text(Player::Player1), start);
+        assert_eq!(Board6::from_text(start), Ok((board, Player::Player1)));
+        assert_eq!(start[..36].parse(), Ok(board));
+        assert_eq!(
+            Board10::from_text(start),
+            Err(ParseBoardError::Length {
+                expected: 100,
+                found: 36
+            })
+        );
+        assert_eq!(
+            SizedBoard::<4>::default().to_string(),
+            "   a b c d\n 1 - - - -\n 2 - O X -\n 3 - X O -\n 4 - - - -\n"
+        );
+    }
+}
@@ -0,0 +1,68 @@
+//! Random keys for Zobrist hashing, generated at compile time so that hashes are stable
+//! across runs.
+
+const SEED: u64 = 0x4c69_6272_6576_6572;
+
+const fn splitmix64(state: u64) -> (u64, u64) {
+    let state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    let mut z = state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    (state, z ^ (z >> 31))
+}
+
+/// One key per square for each player, and last for a blocked square
+pub(crate) const KEYS: [[u64; 64]; 3] = {
+    let mut keys = [[0; 64]; 3];
+    let mut state = SEED;
+    let mut player = 0;
+    while player < 3 {
+        let mut idx = 0;
+        while idx < 64 {
+            let (next, key) = splitmix64(state);
+            state = next;
+            keys[player][idx] = key;
+            idx += 1;
+        }
+        player += 1;
+    }
+    keys
+};
+
+/// Mixed in when player 2 is to move
+pub(crate) const SIDE: u64 = splitmix64(!SEED).1;
+
+/// Hash of the given bitboards, computed from scratch.
+pub(crate) fn hash(p1: u64, p2: u64, blocked: u64) -> u64 {
+    super::Bits(p1).fold(0, |acc, idx| acc ^ KEYS[0][idx as usize])
+        ^ super::Bits(p2).fold(0, |acc, idx| acc ^ KEYS[1][idx as usize])
+        ^ blocked_hash(blocked)
+}
+
+/// Hash of the blocked squares alone.
+pub(crate) fn blocked_hash(blocked: u64) -> u64 {
+    super::Bits(blocked).fold(0, |acc, idx| acc ^ KEYS[2][idx as usize])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keys_are_distinct() {
+        let mut all: Vec<u64> = KEYS.iter().flat_map(|keys| keys.iter().copied()).collect();
+        all.push(SIDE);
+        all.sort_unstable();
+        all.dedup();
+        assert_eq!(all.len(), 193);
+        assert!(!all.contains(&0));
+    }
+
+    #[test]
+    fn test_hash() {
+        assert_eq!(hash(0, 0, 0), 0);
+        assert_eq!(hash(1 << 5, 0, 0), KEYS[0][5]);
+        assert_eq!(hash(1 << 5, 1 << 5, 0), KEYS[0][5] ^ KEYS[1][5]);
+        assert_eq!(hash(0, 0, 1 << 5), KEYS[2][5]);
+    }
+}
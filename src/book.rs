@@ -0,0 +1,444 @@
+//! Opening book built from known lines and learnt from finished games.
+//!
+//! Positions are stored in their canonical orientation, so lines that only differ by a
+//! rotation or reflection of the board share their entries.
+//!
+//! The text format has one line of play per line of text, written as concatenated squares
+//! from the standard start position, e.g. `f5d6c3d3c4`. Columns are `a` to `h` and rows
+//! `1` to `8`; whitespace between squares is allowed, forced passes are left out, and
+//! anything after a `#` is a comment.
+
+use crate::board::{Board, Player, Symmetry};
+use crate::error::MoveError;
+use crate::game::{Game, Ply, Status};
+use crate::position::{ParsePositionError, Position};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::io::{self, BufRead};
+
+/// Number of plies of a game that are learnt unless set otherwise
+pub const DEFAULT_BOOK_DEPTH: usize = 20;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct BookMove {
+    pub pos: Position,
+    /// How often the move was seen in loaded lines and learnt games
+    pub weight: u32,
+    /// Results of learnt games for the side playing the move
+    pub wins: u32,
+    pub draws: u32,
+    pub losses: u32,
+}
+
+impl BookMove {
+    fn new(pos: Position) -> Self {
+        Self {
+            pos,
+            weight: 0,
+            wins: 0,
+            draws: 0,
+            losses: 0,
+        }
+    }
+
+    /// Expected result for the side playing the move, from 0 for a sure loss to 1 for a sure
+    /// win. Every move starts out as if it had been drawn once.
+    pub fn score(&self) -> f64 {
+        let games = (self.wins + self.draws + self.losses) as f64;
+        (self.wins as f64 + 0.5 * self.draws as f64 + 0.5) / (games + 1.0)
+    }
+}
+
+#[derive(Debug)]
+pub enum BookError {
+    /// `column` (counted from 1) of `line` does not hold a square
+    Syntax {
+        line: usize,
+        column: usize,
+    },
+    /// Move number `ply` (counted from 1) of `line` is not legal
+    IllegalMove {
+        line: usize,
+        ply: usize,
+        error: MoveError,
+    },
+    Io(io::Error),
+}
+
+impl fmt::Display for BookError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BookError::Syntax { line, column } => {
+                write!(f, "line {}, column {}: expected a square", line, column)
+            }
+            BookError::IllegalMove { line, ply, error } => {
+                write!(f, "line {}, move {}: {}", line, ply, error)
+            }
+            BookError::Io(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl Error for BookError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            BookError::IllegalMove { error, .. } => Some(error),
+            BookError::Io(err) => Some(err),
+            BookError::Syntax { .. } => None,
+        }
+    }
+}
+
+impl From<io::Error> for BookError {
+    fn from(err: io::Error) -> Self {
+        BookError::Io(err)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct OpeningBook {
+    depth: usize,
+    /// Candidate moves of each canonical position, in the canonical orientation
+    entries: HashMap<(Board, Player), Vec<BookMove>>,
+}
+
+impl Default for OpeningBook {
+    fn default() -> Self {
+        Self::new(DEFAULT_BOOK_DEPTH)
+    }
+}
+
+impl OpeningBook {
+    /// Creates an empty book that learns the first `depth` plies of each game.
+    pub fn new(depth: usize) -> Self {
+        Self {
+            depth,
+            entries: HashMap::new(),
+        }
+    }
+
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Number of positions in the book.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn from_text(text: &str) -> Result<Self, BookError> {
+        Self::load(text.as_bytes())
+    }
+
+    /// Reads a book in the text format, keeping every move of every line.
+    pub fn load<R: BufRead>(reader: R) -> Result<Self, BookError> {
+        let mut book = Self::default();
+        for (i, line) in reader.lines().enumerate() {
+            let line = line?;
+            let moves = parse_line(&line).map_err(|column| BookError::Syntax {
+                line: i + 1,
+                column,
+            })?;
+            book.add_line(&moves)
+                .map_err(|(ply, error)| BookError::IllegalMove {
+                    line: i + 1,
+                    ply: ply + 1,
+                    error,
+                })?;
+        }
+        Ok(book)
+    }
+
+    /// Adds every move of a line played from the start position, or returns the index of the
+    /// first illegal move and leaves the book untouched.
+    pub fn add_line(&mut self, moves: &[Position]) -> Result<(), (usize, MoveError)> {
+        let mut game = Game::new();
+        for (ply, &pos) in moves.iter().enumerate() {
+            game.play(pos).map_err(|error| (ply, error))?;
+        }
+        self.record(Board::default(), Player::Player1, moves, moves.len(), None)
+    }
+
+    /// Learns the opening of a finished game: its first [`depth`](Self::depth) moves gain
+    /// weight and are credited with the game's result. The moves are replayed from the
+    /// game's [`start`](Game::start), which need not be the standard start position.
+    pub fn learn(&mut self, game: &Game) {
+        let winner = match game.status() {
+            Status::Finished { winner, .. } => winner,
+            _ => return,
+        };
+        let moves: Vec<Position> = game
+            .history()
+            .iter()
+            .filter_map(|ply| match *ply {
+                Ply::Move(_, pos) => Some(pos),
+                Ply::Pass(_) => None,
+            })
+            .collect();
+        let (start, player) = game.start();
+        self.record(start.clone(), player, &moves, self.depth, Some(winner))
+            .expect("the moves of a game replay from its start");
+    }
+
+    /// Candidate moves for `player` on `board`, best first: moves are ranked by their
+    /// [`score`](BookMove::score), and then by weight.
+    pub fn candidates(&self, board: &Board, player: Player) -> Vec<BookMove> {
+        let (canonical, sym) = board.canonical();
+        let back = sym.inverse();
+        let mut moves: Vec<BookMove> = self
+            .entries
+            .get(&(canonical, player))
+            .map(|moves| {
+                moves
+                    .iter()
+                    .map(|mv| BookMove {
+                        pos: mv.pos.transform(back),
+                        ..*mv
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        moves.sort_by(|a, b| {
+            b.score()
+                .partial_cmp(&a.score())
+                .unwrap()
+                .then(b.weight.cmp(&a.weight))
+        });
+        moves
+    }
+
+    /// The highest ranked book move for `player` on `board`, if any.
+    pub fn best_move(&self, board: &Board, player: Player) -> Option<Position> {
+        self.candidates(board, player).first().map(|mv| mv.pos)
+    }
+
+    /// Replays up to `limit` of `moves` from `board` with `player` to move, bumping the
+    /// weight of each and crediting it with the result if a winner is given.
+    fn record(
+        &mut self,
+        mut board: Board,
+        mut player: Player,
+        moves: &[Position],
+        limit: usize,
+        result: Option<Option<Player>>,
+    ) -> Result<(), (usize, MoveError)> {
+        for (ply, &pos) in moves.iter().take(limit).enumerate() {
+            if board.legal_moves_mask(player) == 0 {
+                player = player.opponent();
+            }
+            let (canonical, _) = board.canonical();
+            let canonical_pos = canonical_move(&board, &canonical, pos);
+            board
+                .try_place_piece(pos, player)
+                .map_err(|error| (ply, error))?;
+            let entry = self.entries.entry((canonical, player)).or_default();
+            let mv = match entry.iter_mut().find(|mv| mv.pos == canonical_pos) {
+                Some(mv) => mv,
+                None => {
+                    entry.push(BookMove::new(canonical_pos));
+                    entry.last_mut().unwrap()
+                }
+            };
+            mv.weight += 1;
+            match result {
+                Some(Some(winner)) if winner == player => mv.wins += 1,
+                Some(Some(_)) => mv.losses += 1,
+                Some(None) => mv.draws += 1,
+                None => (),
+            }
+            player = player.opponent();
+        }
+        Ok(())
+    }
+}
+
+/// Maps `pos` onto `canonical`. A position that is symmetric in itself can be mapped in
+/// several ways, so the lowest square is taken to keep equivalent moves together.
+fn canonical_move(board: &Board, canonical: &Board, pos: Position) -> Position {
+    Symmetry::ALL
+        .iter()
+        .filter(|sym| board.transform(**sym) == *canonical)
+        .map(|&sym| pos.transform(sym))
+        .min_by_key(|pos| pos.index())
+        .unwrap()
+}
+
+/// Parses a line of concatenated squares, or returns the column where parsing failed.
+fn parse_line(line: &str) -> Result<Vec<Position>, usize> {
+    let line = line.split('#').next().unwrap_or("");
+    let mut moves = vec![];
+    let mut chars = line.char_indices();
+    while let Some((i, col)) = chars.next() {
+        if col.is_whitespace() {
+            continue;
+        }
+        let square: String = match chars.next() {
+            Some((_, row)) => [col, row].iter().collect(),
+            None => col.to_string(),
+        };
+        let pos = square.parse::<Position>().map_err(|err| match err {
+            ParsePositionError::Row(_) | ParsePositionError::Length(_) => i + col.len_utf8() + 1,
+            ParsePositionError::Column(_) => i + 1,
+        })?;
+        moves.push(pos);
+    }
+    Ok(moves)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sq(s: &str) -> Position {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_parse_line() {
+        assert_eq!(
+            parse_line("f5d6 C3  # perpendicular"),
+            Ok(vec![
+                Position::at(4, 5),
+                Position::at(5, 3),
+                Position::at(2, 2)
+            ])
+        );
+        assert_eq!(parse_line("  # only a comment"), Ok(vec![]));
+        assert_eq!(parse_line("f5x6"), Err(3));
+        assert_eq!(parse_line("f5d9"), Err(4));
+        assert_eq!(parse_line("f5d"), Err(4));
+    }
+
+    #[test]
+    fn test_load_and_lookup() {
+        let book = OpeningBook::from_text("f5d6c3\nf5f6\n\nf5d6c5 # another\n").unwrap();
+        let start = Board::default();
+        let candidates = book.candidates(&start, Player::Player1);
+        assert_eq!(candidates.len(), 1);
+        // The start position is symmetric, so any of the four equivalent openings may be given
+        assert!(start.is_legal_move(candidates[0].pos, Player::Player1));
+        assert_eq!(candidates[0].weight, 3);
+
+        let mut board = start.clone();
+        board.place_piece(sq("f5"), Player::Player1);
+        let replies: Vec<_> = book
+            .candidates(&board, Player::Player2)
+            .iter()
+            .map(|mv| (mv.pos, mv.weight))
+            .collect();
+        assert_eq!(replies, vec![(sq("d6"), 2), (sq("f6"), 1)]);
+        assert_eq!(book.best_move(&board, Player::Player2), Some(sq("d6")));
+        assert_eq!(book.best_move(&board, Player::Player1), None);
+    }
+
+    #[test]
+    fn test_symmetric_lines_share_entries() {
+        // The four first moves are all the same move up to symmetry
+        let book = OpeningBook::from_text("f5\nd3\nc4\ne6").unwrap();
+        assert_eq!(book.len(), 1);
+        let candidates = book.candidates(&Board::default(), Player::Player1);
+        assert_eq!(candidates.len(), 1);
+        assert_eq!(candidates[0].weight, 4);
+
+        // A reply learnt after f5 is found after d3 in its mirrored form
+        let book = OpeningBook::from_text("f5d6").unwrap();
+        let mut board = Board::default();
+        board.place_piece(sq("d3"), Player::Player1);
+        let reply = book.best_move(&board, Player::Player2).unwrap();
+        assert!(board.is_legal_move(reply, Player::Player2));
+        assert_eq!(reply, sq("c5"));
+    }
+
+    #[test]
+    fn test_load_errors() {
+        match OpeningBook::from_text("f5d6\nf5z6") {
+            Err(BookError::Syntax { line: 2, column: 3 }) => (),
+            other => panic!("unexpected {:?}", other),
+        }
+        match OpeningBook::from_text("f5d6\nf5d6d6") {
+            Err(BookError::IllegalMove {
+                line: 2,
+                ply: 3,
+                error: MoveError::Occupied,
+            }) => (),
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_rejected_line_leaves_book_untouched() {
+        let mut book = OpeningBook::from_text("f5d6").unwrap();
+        assert_eq!(
+            book.add_line(&[sq("f5"), sq("d6"), sq("d6")]),
+            Err((2, MoveError::Occupied))
+        );
+        assert_eq!(book.len(), 2);
+        let candidates = book.candidates(&Board::default(), Player::Player1);
+        assert_eq!(candidates[0].weight, 1);
+    }
+
+    #[test]
+    fn test_learn() {
+        let mut book = OpeningBook::from_text("f5d6\nf5d6\nf5f6").unwrap();
+        let mut after_f5 = Board::default();
+        after_f5.place_piece(sq("f5"), Player::Player1);
+        assert_eq!(book.best_move(&after_f5, Player::Player2), Some(sq("d6")));
+
+        // Unfinished games teach nothing
+        let mut game = Game::new();
+        game.play(sq("f5")).unwrap();
+        game.play(sq("d6")).unwrap();
+        book.learn(&game);
+        assert_eq!(book.candidates(&after_f5, Player::Player2)[0].weight, 2);
+
+        // Player 2 loses this game, so d6 gives way to f6
+        while !game.is_over() {
+            let pos = game.legal_moves().next().unwrap();
+            game.play(pos).unwrap();
+        }
+        assert!(matches!(
+            game.status(),
+            Status::Finished {
+                winner: Some(Player::Player1),
+                ..
+            }
+        ));
+        book.learn(&game);
+        let candidates = book.candidates(&after_f5, Player::Player2);
+        assert_eq!(candidates[0].pos, sq("f6"));
+        let d6 = candidates[1];
+        assert_eq!(d6.pos, sq("d6"));
+        assert_eq!((d6.weight, d6.wins, d6.draws, d6.losses), (3, 0, 0, 1));
+        assert!(d6.score() < 0.5);
+
+        let f5 = book.candidates(&Board::default(), Player::Player1)[0];
+        assert_eq!((f5.weight, f5.wins), (4, 1));
+    }
+
+    #[test]
+    fn test_learn_from_custom_start() {
+        let mut start = Board::default();
+        start.place_piece(sq("f5"), Player::Player1);
+        start.place_piece(sq("f6"), Player::Player2);
+        let mut game = Game::from_board(start.clone(), Player::Player1);
+        let first = game.legal_moves().next().unwrap();
+        while !game.is_over() {
+            let pos = game.legal_moves().next().unwrap();
+            game.play(pos).unwrap();
+        }
+        let mut book = OpeningBook::new(4);
+        book.learn(&game);
+        assert_eq!(book.len(), 4);
+        let candidates = book.candidates(&start, Player::Player1);
+        assert_eq!(candidates.len(), 1);
+        assert_eq!(candidates[0].pos, first);
+        assert!(book
+            .candidates(&Board::default(), Player::Player1)
+            .is_empty());
+    }
+}
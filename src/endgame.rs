@@ -0,0 +1,393 @@
+//! Exact solver for the last empty squares of a game.
+//!
+//! Scores are final disc differentials from the point of view of the side to move, with the
+//! remaining empty squares going to the winner.
+
+use crate::board::bitboard::{self, Bits};
+use crate::board::{Board, Player};
+use crate::position::Position;
+use std::cmp::Ordering;
+
+/// Below this many empty squares moves are tried straight off the empty-square list instead
+/// of being generated and sorted.
+const SHALLOW_EMPTIES: usize = 6;
+
+/// The bounds of an alpha-beta search. Scores strictly inside the window are exact; a score
+/// at or below `alpha` is an upper bound and a score at or above `beta` a lower bound.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Window {
+    pub alpha: i32,
+    pub beta: i32,
+}
+
+impl Window {
+    /// Finds the exact score
+    pub const FULL: Window = Window::new(-65, 65);
+    /// Only tells a win, a draw and a loss apart
+    pub const WLD: Window = Window::new(-1, 1);
+
+    pub const fn new(alpha: i32, beta: i32) -> Self {
+        Self { alpha, beta }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    Win,
+    Draw,
+    Loss,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Solution {
+    /// The move to play, or `None` if the side to move has to pass or the game is over
+    pub best_move: Option<Position>,
+    /// Final disc differential for the side to move, bounded by the search window
+    pub score: i32,
+    /// Number of positions visited
+    pub nodes: u64,
+}
+
+impl Solution {
+    pub fn outcome(&self) -> Outcome {
+        match self.score.cmp(&0) {
+            Ordering::Greater => Outcome::Win,
+            Ordering::Equal => Outcome::Draw,
+            Ordering::Less => Outcome::Loss,
+        }
+    }
+}
+
+/// Solves `board` with `player` to move within `window`.
+pub fn solve(board: &Board, player: Player, window: Window) -> Solution {
+    let mut solver = Solver::new(board);
+    let own = board.discs(player);
+    let opponent = board.discs(player.opponent());
+    let (score, best) = solver.root(own, opponent, window.alpha, window.beta);
+    Solution {
+        best_move: best.map(|idx| Position { idx }),
+        score,
+        nodes: solver.nodes,
+    }
+}
+
+/// Solves `board` with `player` to move, only finding out whether the game is won, drawn or
+/// lost. The score of the returned solution is `1`, `0` or `-1`.
+pub fn solve_wld(board: &Board, player: Player) -> Solution {
+    let mut solution = solve(board, player, Window::WLD);
+    solution.score = solution.score.signum();
+    solution
+}
+
+/// Final disc differential, with the `empties` empty squares going to the winner.
+fn final_score(own: u64, opponent: u64, empties: usize) -> i32 {
+    let own = own.count_ones() as i32;
+    let opponent = opponent.count_ones() as i32;
+    let empties = empties as i32;
+    match own.cmp(&opponent) {
+        Ordering::Greater => own - opponent + empties,
+        Ordering::Less => own - opponent - empties,
+        Ordering::Equal => 0,
+    }
+}
+
+/// Bit of the board quadrant holding `idx`, used to track the parity of each quadrant.
+fn quadrant(idx: u8) -> u8 {
+    1 << (((idx >> 4) & 0b10) | ((idx >> 2) & 0b1))
+}
+
+struct Solver {
+    nodes: u64,
+    empties: Vec<u8>,
+    /// Blocked squares, where no move can go
+    blocked: u64,
+    /// One bit per quadrant, set when the quadrant has an odd number of empty squares
+    parity: u8,
+}
+
+impl Solver {
+    fn new(board: &Board) -> Self {
+        let empties: Vec<u8> = Bits(board.empty_squares()).collect();
+        let parity = empties.iter().fold(0, |acc, &idx| acc ^ quadrant(idx));
+        Self {
+            nodes: 0,
+            empties,
+            blocked: board.blocked_squares(),
+            parity,
+        }
+    }
+
+    /// Every square `own` can move to, leaving out the blocked ones.
+    fn moves(&self, own: u64, opponent: u64) -> u64 {
+        bitboard::moves(own, opponent) & !self.blocked
+    }
+
+    fn root(&mut self, own: u64, opponent: u64, mut alpha: i32, beta: i32) -> (i32, Option<u8>) {
+        self.nodes += 1;
+        let moves = self.moves(own, opponent);
+        if moves == 0 {
+            let score = if self.moves(opponent, own) == 0 {
+                final_score(own, opponent, self.empties.len())
+            } else {
+                -self.search(opponent, own, -beta, -alpha)
+            };
+            return (score, None);
+        }
+        let mut best = (i32::MIN, None);
+        for idx in self.ordered_moves(own, opponent, moves) {
+            let score = self.play(idx, own, opponent, alpha, beta);
+            if score > best.0 {
+                best = (score, Some(idx));
+            }
+            alpha = alpha.max(score);
+            if alpha >= beta {
+                break;
+            }
+        }
+        best
+    }
+
+    fn search(&mut self, own: u64, opponent: u64, mut alpha: i32, beta: i32) -> i32 {
+        if self.empties.len() <= SHALLOW_EMPTIES {
+            return self.search_shallow(own, opponent, alpha, beta, false);
+        }
+        self.nodes += 1;
+        let moves = self.moves(own, opponent);
+        if moves == 0 {
+            if self.moves(opponent, own) == 0 {
+                return final_score(own, opponent, self.empties.len());
+            }
+            return -self.search(opponent, own, -beta, -alpha);
+        }
+        let mut best = i32::MIN;
+        for idx in self.ordered_moves(own, opponent, moves) {
+            let score = self.play(idx, own, opponent, alpha, beta);
+            best = best.max(score);
+            alpha = alpha.max(score);
+            if alpha >= beta {
+                break;
+            }
+        }
+        best
+    }
+
+    /// Plays `idx`, searches the resulting position and returns its score for the mover.
+    fn play(&mut self, idx: u8, own: u64, opponent: u64, alpha: i32, beta: i32) -> i32 {
+        let flips = bitboard::flips(idx, own, opponent);
+        let i = self.empties.iter().position(|&sq| sq == idx).unwrap();
+        self.remove(i);
+        let score = -self.search(opponent ^ flips, own | flips | 1 << idx, -beta, -alpha);
+        self.restore(i, idx);
+        score
+    }
+
+    /// Sorts `moves` fastest-first: moves leaving the opponent the fewest replies come first,
+    /// and ties go to moves in quadrants with an odd number of empty squares.
+    fn ordered_moves(&self, own: u64, opponent: u64, moves: u64) -> Vec<u8> {
+        let mut ordered: Vec<(u32, bool, u8)> = Bits(moves)
+            .map(|idx| {
+                let flips = bitboard::flips(idx, own, opponent);
+                let replies = self.moves(opponent ^ flips, own | flips | 1 << idx);
+                let even = self.parity & quadrant(idx) == 0;
+                (replies.count_ones(), even, idx)
+            })
+            .collect();
+        ordered.sort_unstable();
+        ordered.into_iter().map(|(_, _, idx)| idx).collect()
+    }
+
+    /// Search near the end of the game, trying squares straight off the empty-square list, those
+    /// in odd quadrants first.
+    fn search_shallow(
+        &mut self,
+        own: u64,
+        opponent: u64,
+        mut alpha: i32,
+        beta: i32,
+        passed: bool,
+    ) -> i32 {
+        self.nodes += 1;
+        if self.empties.is_empty() {
+            return final_score(own, opponent, self.empties.len());
+        }
+        let mut best = i32::MIN;
+        for &odd in &[true, false] {
+            for i in 0..self.empties.len() {
+                let idx = self.empties[i];
+                if (self.parity & quadrant(idx) != 0) != odd {
+                    continue;
+                }
+                let flips = bitboard::flips(idx, own, opponent);
+                if flips == 0 {
+                    continue;
+                }
+                self.remove(i);
+                let score = -self.search_shallow(
+                    opponent ^ flips,
+                    own | flips | 1 << idx,
+                    -beta,
+                    -alpha,
+                    false,
+                );
+                self.restore(i, idx);
+                best = best.max(score);
+                alpha = alpha.max(score);
+                if alpha >= beta {
+                    return best;
+                }
+            }
+        }
+        if best == i32::MIN {
+            if passed {
+                return final_score(own, opponent, self.empties.len());
+            }
+            return -self.search_shallow(opponent, own, -beta, -alpha, true);
+        }
+        best
+    }
+
+    fn remove(&mut self, i: usize) {
+        let idx = self.empties.swap_remove(i);
+        self.parity ^= quadrant(idx);
+    }
+
+    fn restore(&mut self, i: usize, idx: u8) {
+        self.empties.push(idx);
+        let last = self.empties.len() - 1;
+        self.empties.swap(i, last);
+        self.parity ^= quadrant(idx);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::Disc;
+    use crate::rng::{random_position, Rng};
+
+    /// Plain minimax over every line, without any pruning or ordering
+    fn brute_force(board: &Board, player: Player) -> i32 {
+        let opponent = player.opponent();
+        let moves: Vec<_> = board.all_legal_moves(player).collect();
+        if moves.is_empty() {
+            if board.legal_moves_mask(opponent) == 0 {
+                return final_score(
+                    board.discs(player),
+                    board.discs(opponent),
+                    board.empty_squares().count_ones() as usize,
+                );
+            }
+            return -brute_force(board, opponent);
+        }
+        moves
+            .into_iter()
+            .map(|pos| {
+                let mut child = board.clone();
+                child.place_piece(pos, player);
+                -brute_force(&child, opponent)
+            })
+            .max()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_quadrants() {
+        assert_eq!(quadrant(Position::at(0, 0).idx), 0b0001);
+        assert_eq!(quadrant(Position::at(3, 7).idx), 0b0010);
+        assert_eq!(quadrant(Position::at(4, 3).idx), 0b0100);
+        assert_eq!(quadrant(Position::at(7, 4).idx), 0b1000);
+    }
+
+    #[test]
+    fn test_final_score_gives_empties_to_winner() {
+        let mut board = Board::empty();
+        board.set_piece(Position::at(0, 0), Disc::Player1);
+        board.set_piece(Position::at(0, 1), Disc::Player1);
+        board.set_piece(Position::at(7, 7), Disc::Player2);
+        let solution = solve(&board, Player::Player1, Window::FULL);
+        assert_eq!(solution.score, 63 - 1);
+        assert_eq!(solution.best_move, None);
+        assert_eq!(solve(&board, Player::Player2, Window::FULL).score, -62);
+        assert_eq!(
+            solve(&Board::empty(), Player::Player1, Window::FULL).score,
+            0
+        );
+    }
+
+    #[test]
+    fn test_blocked_squares() {
+        // Player 1 could only flip b1 by playing on the blocked c1, so the game is over
+        let mut board = Board::empty();
+        board.set_piece(Position::at(0, 0), Disc::Player1);
+        board.set_piece(Position::at(0, 1), Disc::Player2);
+        board.set_piece(Position::at(0, 2), Disc::Blocked);
+        let solution = solve(&board, Player::Player1, Window::FULL);
+        assert_eq!((solution.score, solution.best_move), (0, None));
+
+        // Blocked squares do not go to the winner
+        board.set_piece(Position::at(7, 7), Disc::Player1);
+        board.set_blocked(1 << 2 | 0b1111 << 4);
+        assert_eq!(solve(&board, Player::Player1, Window::FULL).score, 1 + 56);
+    }
+
+    #[test]
+    fn test_matches_brute_force() {
+        let mut rng = Rng::new(0x9e37_79b9_7f4a_7c15);
+        let mut solved = 0;
+        while solved < 20 {
+            let empties = 4 + solved % 6;
+            let (board, player) = match random_position(&mut rng, empties) {
+                Some(position) => position,
+                None => continue,
+            };
+            let expected = brute_force(&board, player);
+            let solution = solve(&board, player, Window::FULL);
+            assert_eq!(
+                solution.score, expected,
+                "{:?} to move on {:?}",
+                player, board
+            );
+            if let Some(pos) = solution.best_move {
+                let mut child = board.clone();
+                assert!(child.try_place_piece(pos, player).is_ok());
+                assert_eq!(-brute_force(&child, player.opponent()), expected);
+            }
+            assert_eq!(solve_wld(&board, player).score, expected.signum());
+            solved += 1;
+        }
+    }
+
+    #[test]
+    fn test_windows_bound_the_score() {
+        let mut rng = Rng::new(0x0123_4567_89ab_cdef);
+        let mut solved = 0;
+        while solved < 5 {
+            let (board, player) = match random_position(&mut rng, 10) {
+                Some(position) => position,
+                None => continue,
+            };
+            let exact = solve(&board, player, Window::FULL).score;
+            let low = solve(&board, player, Window::new(exact, exact + 2)).score;
+            assert!(low <= exact);
+            let high = solve(&board, player, Window::new(exact - 2, exact)).score;
+            assert!(high >= exact);
+            let inside = solve(&board, player, Window::new(exact - 1, exact + 1)).score;
+            assert_eq!(inside, exact);
+            solved += 1;
+        }
+    }
+
+    #[test]
+    fn test_wld_is_cheaper() {
+        let mut rng = Rng::new(0xdead_beef_cafe_f00d);
+        let (board, player) = loop {
+            if let Some(position) = random_position(&mut rng, 14) {
+                break position;
+            }
+        };
+        let exact = solve(&board, player, Window::FULL);
+        let wld = solve_wld(&board, player);
+        assert_eq!(wld.outcome(), exact.outcome());
+        assert!(wld.nodes <= exact.nodes);
+    }
+}
@@ -0,0 +1,31 @@
+use std::error::Error;
+use std::fmt;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum MoveError {
+    /// The target square already holds a disc
+    Occupied,
+    /// The target square is blocked
+    Blocked,
+    /// Placing a disc on the target square would not flip anything
+    NoFlips,
+    /// A player tried to move while it was their opponent's turn
+    NotYourTurn,
+    /// Neither player can move any more
+    GameOver,
+}
+
+impl fmt::Display for MoveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let msg = match self {
+            MoveError::Occupied => "square is already occupied",
+            MoveError::Blocked => "square is blocked",
+            MoveError::NoFlips => "move does not flip any discs",
+            MoveError::NotYourTurn => "it is not this player's turn",
+            MoveError::GameOver => "the game is over",
+        };
+        f.write_str(msg)
+    }
+}
+
+impl Error for MoveError {}
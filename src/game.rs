@@ -0,0 +1,364 @@
+use crate::board::{Board, Player, Rules};
+use crate::error::MoveError;
+use crate::position::Position;
+use std::cmp::Ordering;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Status {
+    InProgress,
+    /// The given player had no legal move and the turn went back to their opponent
+    Pass(Player),
+    /// `score` holds the disc counts of player 1 and player 2; `winner` is `None` on a draw
+    Finished {
+        winner: Option<Player>,
+        score: (usize, usize),
+    },
+}
+
+/// A ply of a game on a board whose squares are `P`, [`Position`] for the standard board.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Ply<P = Position> {
+    Move(Player, P),
+    Pass(Player),
+}
+
+/// A game on the standard [`Board`], or on any other board `B` with the same [`Rules`], such
+/// as a [`SizedBoard`](crate::SizedBoard).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Game<B: Rules = Board> {
+    /// The position the game started from and the side that moved first there
+    start: (B, Player),
+    board: B,
+    to_move: Player,
+    history: Vec<Ply<B::Position>>,
+    status: Status,
+    /// The moves played, to take them back
+    made: Vec<B::Move>,
+    /// Squares of the moves taken back, the most recent last
+    undone: Vec<B::Position>,
+}
+
+impl<B: Rules> Default for Game<B> {
+    fn default() -> Self {
+        Self::from_board(B::default(), Player::Player1)
+    }
+}
+
+impl Game {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<B: Rules> Game<B> {
+    /// Starts a game from an arbitrary position, resolving a forced pass or
+    /// the end of the game straight away.
+    pub fn from_board(board: B, to_move: Player) -> Self {
+        let mut game = Self {
+            start: (board.clone(), to_move),
+            board,
+            to_move,
+            history: vec![],
+            status: Status::InProgress,
+            made: vec![],
+            undone: vec![],
+        };
+        game.resolve_turn();
+        game
+    }
+
+    pub fn board(&self) -> &B {
+        &self.board
+    }
+
+    /// The board the game started from and the side to move there, before any forced pass.
+    pub fn start(&self) -> (&B, Player) {
+        (&self.start.0, self.start.1)
+    }
+
+    pub fn to_move(&self) -> Player {
+        self.to_move
+    }
+
+    pub fn status(&self) -> Status {
+        self.status
+    }
+
+    pub fn history(&self) -> &[Ply<B::Position>] {
+        &self.history
+    }
+
+    pub fn is_over(&self) -> bool {
+        matches!(self.status, Status::Finished { .. })
+    }
+
+    pub fn legal_moves(&self) -> impl Iterator<Item = B::Position> + '_ {
+        let player = self.to_move;
+        self.board
+            .legal_moves(player)
+            .into_iter()
+            .filter(move |_| !self.is_over())
+    }
+
+    /// Plays `pos` for the side to move, returning what the move changed. An
+    /// illegal move is rejected and leaves the game untouched.
+    ///
+    /// Playing a move forgets the moves that could be redone.
+    pub fn play(&mut self, pos: B::Position) -> Result<B::Move, MoveError> {
+        let mv = self.make(pos)?;
+        self.undone.clear();
+        Ok(mv)
+    }
+
+    /// Like [`Game::play`], but also checks that it is `player`'s turn.
+    pub fn play_as(&mut self, player: Player, pos: B::Position) -> Result<B::Move, MoveError> {
+        if self.is_over() {
+            return Err(MoveError::GameOver);
+        }
+        if player != self.to_move {
+            return Err(MoveError::NotYourTurn);
+        }
+        self.play(pos)
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.made.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.undone.is_empty()
+    }
+
+    /// Takes back the last move, along with the pass that may have followed it, and returns
+    /// it. Returns `None` if no move has been played.
+    pub fn undo(&mut self) -> Option<B::Move> {
+        let mv = self.made.pop()?;
+        self.board.unmake(&mv);
+        let (idx, player, pos) = self
+            .history
+            .iter()
+            .enumerate()
+            .rev()
+            .find_map(|(idx, ply)| match *ply {
+                Ply::Move(player, pos) => Some((idx, player, pos)),
+                Ply::Pass(_) => None,
+            })
+            .unwrap();
+        self.history.truncate(idx);
+        self.to_move = player;
+        // A pass right before the move was the status the move answered
+        self.status = match self.history.last() {
+            Some(&Ply::Pass(player)) => Status::Pass(player),
+            _ => Status::InProgress,
+        };
+        self.undone.push(pos);
+        Some(mv)
+    }
+
+    /// Plays the last move taken back again and returns it. Returns `None` if there is
+    /// nothing to redo.
+    pub fn redo(&mut self) -> Option<B::Move> {
+        let pos = self.undone.pop()?;
+        // Nothing was played since the undo, so the move is still legal
+        self.make(pos).ok()
+    }
+
+    fn make(&mut self, pos: B::Position) -> Result<B::Move, MoveError> {
+        if self.is_over() {
+            return Err(MoveError::GameOver);
+        }
+        let mv = self.board.try_place_piece(pos, self.to_move)?;
+        self.history.push(Ply::Move(self.to_move, pos));
+        self.made.push(mv);
+        self.to_move = self.to_move.opponent();
+        self.resolve_turn();
+        Ok(mv)
+    }
+
+    fn has_moves(&self, player: Player) -> bool {
+        self.board.legal_move_count(player) > 0
+    }
+
+    fn resolve_turn(&mut self) {
+        let opponent = self.to_move.opponent();
+        if self.has_moves(self.to_move) {
+            self.status = Status::InProgress;
+        } else if self.has_moves(opponent) {
+            self.history.push(Ply::Pass(self.to_move));
+            self.status = Status::Pass(self.to_move);
+            self.to_move = opponent;
+        } else {
+            let score = (self.board.player1_count(), self.board.player2_count());
+            let winner = match score.0.cmp(&score.1) {
+                Ordering::Greater => Some(Player::Player1),
+                Ordering::Less => Some(Player::Player2),
+                Ordering::Equal => None,
+            };
+            self.status = Status::Finished { winner, score };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::Disc;
+
+    #[test]
+    fn test_new_game() {
+        let game = Game::new();
+        assert_eq!(game.board(), &Board::default());
+        assert_eq!(game.to_move(), Player::Player1);
+        assert_eq!(game.status(), Status::InProgress);
+        assert!(game.history().is_empty());
+        assert_eq!(game.legal_moves().count(), 4);
+    }
+
+    #[test]
+    fn test_play_alternates_turns() {
+        let mut game = Game::new();
+        let mv = game.play(Position::at(2, 3)).unwrap();
+        assert_eq!(mv.flipped().collect::<Vec<_>>(), vec![Position::at(3, 3)]);
+        assert_eq!(game.to_move(), Player::Player2);
+        assert_eq!(
+            game.history(),
+            &[Ply::Move(Player::Player1, Position::at(2, 3))]
+        );
+        assert_eq!(game.play(Position::at(2, 3)), Err(MoveError::Occupied));
+        assert!(game.play(Position::at(2, 2)).is_ok());
+        assert_eq!(game.to_move(), Player::Player1);
+    }
+
+    #[test]
+    fn test_illegal_move_leaves_game_untouched() {
+        let mut game = Game::new();
+        let before = game.clone();
+        assert_eq!(game.play(Position::at(0, 0)), Err(MoveError::NoFlips));
+        assert_eq!(game.play(Position::at(3, 3)), Err(MoveError::Occupied));
+        assert_eq!(game, before);
+    }
+
+    #[test]
+    fn test_play_as() {
+        let mut game = Game::new();
+        assert_eq!(
+            game.play_as(Player::Player2, Position::at(2, 4)),
+            Err(MoveError::NotYourTurn)
+        );
+        assert!(game.play_as(Player::Player1, Position::at(2, 3)).is_ok());
+        assert!(game.play_as(Player::Player2, Position::at(2, 2)).is_ok());
+    }
+
+    #[test]
+    fn test_forced_pass() {
+        // Player 2 cannot answer a1 with anything, so the turn returns to player 1
+        let mut board = Board::empty();
+        board.set_piece(Position::at(0, 1), Disc::Player2);
+        board.set_piece(Position::at(0, 2), Disc::Player1);
+        board.set_piece(Position::at(1, 0), Disc::Player2);
+        board.set_piece(Position::at(2, 0), Disc::Player1);
+        for col in 0..6 {
+            board.set_piece(Position::at(7, col), Disc::Player1);
+        }
+        board.set_piece(Position::at(7, 6), Disc::Player2);
+        let mut game = Game::from_board(board, Player::Player1);
+        assert!(game.play(Position::at(0, 0)).is_ok());
+        assert_eq!(game.status(), Status::Pass(Player::Player2));
+        assert_eq!(game.to_move(), Player::Player1);
+        assert_eq!(game.history().last(), Some(&Ply::Pass(Player::Player2)));
+    }
+
+    #[test]
+    fn test_game_over() {
+        let mut board = Board::empty();
+        board.set_piece(Position::at(0, 0), Disc::Player1);
+        board.set_piece(Position::at(0, 1), Disc::Player2);
+        let mut game = Game::from_board(board, Player::Player1);
+        assert_eq!(game.status(), Status::InProgress);
+        assert!(game.play(Position::at(0, 2)).is_ok());
+        assert_eq!(
+            game.status(),
+            Status::Finished {
+                winner: Some(Player::Player1),
+                score: (3, 0)
+            }
+        );
+        assert!(game.is_over());
+        assert_eq!(game.legal_moves().count(), 0);
+        assert_eq!(game.play(Position::at(5, 5)), Err(MoveError::GameOver));
+    }
+
+    #[test]
+    fn test_undo_redo() {
+        let mut game = Game::new();
+        assert!(!game.can_undo());
+        assert_eq!(game.undo(), None);
+        let first = game.play(Position::at(2, 3)).unwrap();
+        let after_first = game.clone();
+        let second = game.play(Position::at(2, 2)).unwrap();
+        let after_second = game.clone();
+
+        assert_eq!(game.undo(), Some(second));
+        assert_eq!(game.board(), after_first.board());
+        assert_eq!(game.to_move(), Player::Player2);
+        assert_eq!(game.history(), after_first.history());
+        assert_eq!(game.undo(), Some(first));
+        assert_eq!(game.board(), &Board::default());
+        assert!(game.history().is_empty());
+        assert!(game.can_redo());
+
+        assert_eq!(game.redo(), Some(first));
+        assert_eq!(game.redo(), Some(second));
+        assert_eq!(game, after_second);
+        assert_eq!(game.redo(), None);
+
+        // A new move drops what could be redone
+        game.undo();
+        game.play(Position::at(2, 4)).unwrap();
+        assert!(!game.can_redo());
+    }
+
+    #[test]
+    fn test_undo_pass_and_game_end() {
+        let mut board = Board::empty();
+        board.set_piece(Position::at(0, 1), Disc::Player2);
+        board.set_piece(Position::at(0, 2), Disc::Player1);
+        board.set_piece(Position::at(1, 0), Disc::Player2);
+        board.set_piece(Position::at(2, 0), Disc::Player1);
+        for col in 0..6 {
+            board.set_piece(Position::at(7, col), Disc::Player1);
+        }
+        board.set_piece(Position::at(7, 6), Disc::Player2);
+        let start = Game::from_board(board, Player::Player1);
+        let mut game = start.clone();
+        game.play(Position::at(0, 0)).unwrap();
+        assert_eq!(game.status(), Status::Pass(Player::Player2));
+        game.play(Position::at(7, 7)).unwrap();
+        assert!(game.is_over());
+
+        game.undo();
+        assert_eq!(game.status(), Status::Pass(Player::Player2));
+        assert_eq!(game.to_move(), Player::Player1);
+        assert_eq!(game.history().len(), 2);
+        game.undo();
+        assert_eq!(game.board(), start.board());
+        assert_eq!(game.status(), Status::InProgress);
+        assert!(game.history().is_empty());
+        game.redo();
+        game.redo();
+        assert!(game.is_over());
+    }
+
+    #[test]
+    fn test_from_board_without_moves_is_finished() {
+        let game = Game::from_board(Board::empty(), Player::Player2);
+        assert_eq!(game.start(), (&Board::empty(), Player::Player2));
+        assert_eq!(
+            game.status(),
+            Status::Finished {
+                winner: None,
+                score: (0, 0)
+            }
+        );
+    }
+}
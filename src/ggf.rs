@@ -0,0 +1,551 @@
+//! Reading and writing game records in the Generic Game Format (GGF).
+//!
+//! A record is a list of `TAG[value]` pairs between `(;` and `;)`, e.g.
+//!
+//! ```text
+//! (;GM[Othello]PB[alice]PW[bob]RB[2100.5]TI[15:00]TY[8]
+//! BO[8 -------- -------- -------- ---O*--- ---*O--- -------- -------- -------- *]
+//! B[f5//1.2]W[d6/-2.50/3.1];)
+//! ```
+//!
+//! Moves are written as `square/evaluation/time` where the last two are optional, and `PA`
+//! stands for a pass.
+
+use crate::board::{Board, Player};
+use crate::error::MoveError;
+use crate::game::{Game, Ply, Status};
+use crate::transcript::Transcript;
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum GgfError {
+    /// The text at byte `offset` does not fit the format
+    Syntax {
+        offset: usize,
+        expected: &'static str,
+    },
+    /// The value of `tag` could not be read
+    InvalidTag { tag: String, value: String },
+    /// The board is not an 8x8 board
+    UnsupportedBoard(String),
+    /// Move number `ply` (counted from 1, passes included) is not legal
+    IllegalMove { ply: usize, error: MoveError },
+    /// Move number `ply` is a pass although the player had a legal move
+    IllegalPass { ply: usize },
+}
+
+impl fmt::Display for GgfError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GgfError::Syntax { offset, expected } => {
+                write!(f, "at {}: expected {}", offset, expected)
+            }
+            GgfError::InvalidTag { tag, value } => write!(f, "invalid {}[{}]", tag, value),
+            GgfError::UnsupportedBoard(value) => write!(f, "unsupported board {:?}", value),
+            GgfError::IllegalMove { ply, error } => write!(f, "move {}: {}", ply, error),
+            GgfError::IllegalPass { ply } => {
+                write!(f, "move {}: pass while a move is possible", ply)
+            }
+        }
+    }
+}
+
+impl Error for GgfError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            GgfError::IllegalMove { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct GgfMove {
+    pub ply: Ply,
+    /// Evaluation given by the player, in discs
+    pub eval: Option<f64>,
+    /// Time taken, in seconds
+    pub time: Option<f64>,
+}
+
+impl GgfMove {
+    pub fn new(ply: Ply) -> Self {
+        Self {
+            ply,
+            eval: None,
+            time: None,
+        }
+    }
+}
+
+/// A game record. Tags without a field of their own are kept in `other_tags`, so that they
+/// are written back out.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GgfGame {
+    /// `PC`, where the game was played
+    pub place: Option<String>,
+    /// `DT`
+    pub date: Option<String>,
+    /// `PB` and `PW`
+    pub black: Option<String>,
+    pub white: Option<String>,
+    /// `RB` and `RW`
+    pub black_rating: Option<f64>,
+    pub white_rating: Option<f64>,
+    /// `TI`, the time control of both players, unless `TB` or `TW` give one per player
+    pub time_control: Option<String>,
+    pub black_time_control: Option<String>,
+    pub white_time_control: Option<String>,
+    /// `TY`, e.g. `8` or `8r` for a rated 8x8 game
+    pub game_type: Option<String>,
+    /// `RE`, the result from black's point of view
+    pub result: Option<String>,
+    /// `BO`, the position the game started from
+    pub board: Board,
+    pub to_move: Player,
+    pub moves: Vec<GgfMove>,
+    pub other_tags: Vec<(String, String)>,
+}
+
+impl Default for GgfGame {
+    fn default() -> Self {
+        Self {
+            place: None,
+            date: None,
+            black: None,
+            white: None,
+            black_rating: None,
+            white_rating: None,
+            time_control: None,
+            black_time_control: None,
+            white_time_control: None,
+            game_type: Some("8".to_string()),
+            result: None,
+            board: Board::default(),
+            to_move: Player::Player1,
+            moves: vec![],
+            other_tags: vec![],
+        }
+    }
+}
+
+impl From<&Transcript> for GgfGame {
+    /// A record of the moves of `transcript`, with the result filled in if the game is over.
+    fn from(transcript: &Transcript) -> Self {
+        let result = match transcript.game().status() {
+            Status::Finished { score, .. } => {
+                Some(format!("{:+.2}", score.0 as f64 - score.1 as f64))
+            }
+            _ => None,
+        };
+        Self {
+            result,
+            moves: transcript
+                .plies()
+                .iter()
+                .map(|&ply| GgfMove::new(ply))
+                .collect(),
+            ..Self::default()
+        }
+    }
+}
+
+impl GgfGame {
+    /// Plays the moves from the starting position, checking that each of them is legal.
+    pub fn replay(&self) -> Result<Game, GgfError> {
+        let mut game = Game::from_board(self.board.clone(), self.to_move);
+        // Plies of the game that the record has caught up with; the game fills in forced
+        // passes by itself, which the record may or may not mention
+        let mut done = 0;
+        for (i, mv) in self.moves.iter().enumerate() {
+            let ply = i + 1;
+            match mv.ply {
+                Ply::Move(player, pos) => {
+                    done = game.history().len() + 1;
+                    game.play_as(player, pos)
+                        .map_err(|error| GgfError::IllegalMove { ply, error })?;
+                }
+                Ply::Pass(player) => {
+                    let history = &game.history()[done..];
+                    match history.iter().position(|&p| p == Ply::Pass(player)) {
+                        Some(skip) => done += skip + 1,
+                        None => return Err(GgfError::IllegalPass { ply }),
+                    }
+                }
+            }
+        }
+        Ok(game)
+    }
+
+    /// Reads every record in `text`, ignoring anything between them.
+    pub fn parse_all(text: &str) -> Result<Vec<GgfGame>, GgfError> {
+        let mut games = vec![];
+        let mut offset = 0;
+        while let Some(start) = text[offset..].find("(;") {
+            let (game, len) = parse_game(text, offset + start)?;
+            games.push(game);
+            offset += start + len;
+        }
+        Ok(games)
+    }
+}
+
+impl FromStr for GgfGame {
+    type Err = GgfError;
+
+    /// Reads a single record, checking that its moves are legal.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let start = s.find("(;").ok_or(GgfError::Syntax {
+            offset: 0,
+            expected: "(;",
+        })?;
+        parse_game(s, start).map(|(game, _)| game)
+    }
+}
+
+/// Parses the record starting at byte `start` of `text`, returning it and its length.
+fn parse_game(text: &str, start: usize) -> Result<(GgfGame, usize), GgfError> {
+    let mut game = GgfGame::default();
+    let mut offset = start + 2;
+    loop {
+        let rest = &text[offset..];
+        let trimmed = rest.trim_start();
+        offset += rest.len() - trimmed.len();
+        if trimmed.starts_with(";)") {
+            offset += 2;
+            break;
+        }
+        let open = trimmed.find('[').ok_or(GgfError::Syntax {
+            offset,
+            expected: "TAG[value]",
+        })?;
+        let tag = &trimmed[..open];
+        if tag.is_empty() || !tag.chars().all(|c| c.is_ascii_uppercase()) {
+            return Err(GgfError::Syntax {
+                offset,
+                expected: "TAG[value]",
+            });
+        }
+        let close = trimmed.find(']').ok_or(GgfError::Syntax {
+            offset: offset + open,
+            expected: "]",
+        })?;
+        set_tag(&mut game, tag, &trimmed[open + 1..close])?;
+        offset += close + 1;
+    }
+    game.replay()?;
+    Ok((game, offset - start))
+}
+
+fn set_tag(game: &mut GgfGame, tag: &str, value: &str) -> Result<(), GgfError> {
+    let invalid = || GgfError::InvalidTag {
+        tag: tag.to_string(),
+        value: value.to_string(),
+    };
+    let text = || Some(value.to_string());
+    match tag {
+        "GM" if value != "Othello" => return Err(invalid()),
+        "GM" => (),
+        "PC" => game.place = text(),
+        "DT" => game.date = text(),
+        "PB" => game.black = text(),
+        "PW" => game.white = text(),
+        "RB" => game.black_rating = Some(value.parse().map_err(|_| invalid())?),
+        "RW" => game.white_rating = Some(value.parse().map_err(|_| invalid())?),
+        "TI" => game.time_control = text(),
+        "TB" => game.black_time_control = text(),
+        "TW" => game.white_time_control = text(),
+        "TY" => game.game_type = text(),
+        "RE" => game.result = text(),
+        "BO" if !value.trim_start().starts_with("8 ") => {
+            return Err(GgfError::UnsupportedBoard(value.to_string()))
+        }
+        "BO" => {
+            let (board, to_move) = parse_board(value).ok_or_else(invalid)?;
+            game.board = board;
+            game.to_move = to_move;
+        }
+        "B" | "W" => {
+            let player = if tag == "B" {
+                Player::Player1
+            } else {
+                Player::Player2
+            };
+            game.moves
+                .push(parse_move(player, value).ok_or_else(invalid)?);
+        }
+        _ => game.other_tags.push((tag.to_string(), value.to_string())),
+    }
+    Ok(())
+}
+
+/// Reads the rows of an 8x8 board and the side to move.
+fn parse_board(value: &str) -> Option<(Board, Player)> {
+    let mut tokens: Vec<&str> = value.split_whitespace().skip(1).collect();
+    let to_move = match tokens.pop()? {
+        "*" => Player::Player1,
+        "O" => Player::Player2,
+        _ => return None,
+    };
+    let board = tokens.concat().parse().ok()?;
+    Some((board, to_move))
+}
+
+fn parse_move(player: Player, value: &str) -> Option<GgfMove> {
+    let mut fields = value.split('/');
+    let square = fields.next()?;
+    let ply = if square.eq_ignore_ascii_case("pa") {
+        Ply::Pass(player)
+    } else {
+        Ply::Move(player, square.parse().ok()?)
+    };
+    let eval = match fields.next() {
+        Some("") | None => None,
+        Some(eval) => Some(eval.parse().ok()?),
+    };
+    let time = match fields.next() {
+        Some("") | None => None,
+        Some(time) => Some(parse_time(time)?),
+    };
+    Some(GgfMove { ply, eval, time })
+}
+
+/// Reads seconds, optionally preceded by minutes and hours, as in `1:02:03.5`.
+fn parse_time(time: &str) -> Option<f64> {
+    time.split(':').try_fold(0.0, |total, part| {
+        Some(total * 60.0 + part.parse::<f64>().ok()?)
+    })
+}
+
+impl fmt::Display for GgfGame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "(;GM[Othello]")?;
+        let tags = [
+            ("PC", &self.place),
+            ("DT", &self.date),
+            ("PB", &self.black),
+            ("PW", &self.white),
+        ];
+        for (tag, value) in tags.iter() {
+            if let Some(value) = value {
+                write!(f, "{}[{}]", tag, value)?;
+            }
+        }
+        for (tag, rating) in [("RB", self.black_rating), ("RW", self.white_rating)].iter() {
+            if let Some(rating) = rating {
+                write!(f, "{}[{}]", tag, rating)?;
+            }
+        }
+        let tags = [
+            ("TI", &self.time_control),
+            ("TB", &self.black_time_control),
+            ("TW", &self.white_time_control),
+            ("TY", &self.game_type),
+            ("RE", &self.result),
+        ];
+        for (tag, value) in tags.iter() {
+            if let Some(value) = value {
+                write!(f, "{}[{}]", tag, value)?;
+            }
+        }
+        for (tag, value) in &self.other_tags {
+            write!(f, "{}[{}]", tag, value)?;
+        }
+
+        write!(f, "BO[8")?;
+        let squares = self.board.to_text(self.to_move).replace('X', "*");
+        let (squares, to_move) = squares.split_at(64);
+        for row in 0..8 {
+            write!(f, " {}", &squares[row * 8..row * 8 + 8])?;
+        }
+        write!(f, " {}]", to_move.trim())?;
+
+        for mv in &self.moves {
+            let (tag, square) = match mv.ply {
+                Ply::Move(player, pos) => (player, pos.to_string()),
+                Ply::Pass(player) => (player, "PA".to_string()),
+            };
+            let tag = match tag {
+                Player::Player1 => "B",
+                Player::Player2 => "W",
+            };
+            write!(f, "{}[{}", tag, square)?;
+            if mv.eval.is_some() || mv.time.is_some() {
+                write!(f, "/")?;
+                if let Some(eval) = mv.eval {
+                    write!(f, "{}", eval)?;
+                }
+                if let Some(time) = mv.time {
+                    write!(f, "/{}", time)?;
+                }
+            }
+            write!(f, "]")?;
+        }
+        write!(f, ";)")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::Disc;
+    use crate::position::Position;
+
+    const RECORD: &str = "(;GM[Othello]PC[NIOS]DT[2003.12.15_13:24:03.MST]PB[alice]PW[bob]\
+        RB[2197.18]RW[1735.96]TI[15:00//02:00]TY[8]RE[+12.00]\
+        BO[8 -------- -------- -------- ---O*--- ---*O--- -------- -------- -------- *]\
+        B[F5//0.01]W[d6/-1.50/2.3]B[c3/2/1:05]W[d3];)";
+
+    fn sq(s: &str) -> Position {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_parse() {
+        let game: GgfGame = RECORD.parse().unwrap();
+        assert_eq!(game.place.as_deref(), Some("NIOS"));
+        assert_eq!(game.date.as_deref(), Some("2003.12.15_13:24:03.MST"));
+        assert_eq!(game.black.as_deref(), Some("alice"));
+        assert_eq!(game.white.as_deref(), Some("bob"));
+        assert_eq!(game.black_rating, Some(2197.18));
+        assert_eq!(game.white_rating, Some(1735.96));
+        assert_eq!(game.time_control.as_deref(), Some("15:00//02:00"));
+        assert_eq!(game.game_type.as_deref(), Some("8"));
+        assert_eq!(game.result.as_deref(), Some("+12.00"));
+        assert_eq!(game.board, Board::default());
+        assert_eq!(game.to_move, Player::Player1);
+        assert_eq!(
+            game.moves,
+            vec![
+                GgfMove {
+                    ply: Ply::Move(Player::Player1, sq("f5")),
+                    eval: None,
+                    time: Some(0.01)
+                },
+                GgfMove {
+                    ply: Ply::Move(Player::Player2, sq("d6")),
+                    eval: Some(-1.5),
+                    time: Some(2.3)
+                },
+                GgfMove {
+                    ply: Ply::Move(Player::Player1, sq("c3")),
+                    eval: Some(2.0),
+                    time: Some(65.0)
+                },
+                GgfMove::new(Ply::Move(Player::Player2, sq("d3"))),
+            ]
+        );
+        let transcript: Transcript = "f5d6c3d3".parse().unwrap();
+        assert_eq!(game.replay().unwrap(), *transcript.game());
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let game: GgfGame = RECORD.parse().unwrap();
+        let text = game.to_string();
+        assert_eq!(text.parse(), Ok(game));
+
+        let mut game = GgfGame::default();
+        game.other_tags.push(("KO".to_string(), "9".to_string()));
+        game.board = Board::empty();
+        game.board.set_piece(sq("a1"), Disc::Player2);
+        game.board.set_piece(sq("b1"), Disc::Player1);
+        game.to_move = Player::Player2;
+        game.moves
+            .push(GgfMove::new(Ply::Move(Player::Player2, sq("c1"))));
+        assert_eq!(game.to_string().parse(), Ok(game));
+    }
+
+    #[test]
+    fn test_from_transcript() {
+        let transcript: Transcript = "d3c3b3d2e1d6d7e3f4".parse().unwrap();
+        let game = GgfGame::from(&transcript);
+        assert_eq!(game.result.as_deref(), Some("+13.00"));
+        let parsed: GgfGame = game.to_string().parse().unwrap();
+        assert_eq!(parsed.replay().unwrap(), *transcript.game());
+    }
+
+    #[test]
+    fn test_forced_pass() {
+        // White cannot answer a1 and passes
+        let mut board = Board::empty();
+        board.set_piece(sq("b1"), Disc::Player2);
+        board.set_piece(sq("c1"), Disc::Player1);
+        board.set_piece(sq("a2"), Disc::Player2);
+        board.set_piece(sq("a3"), Disc::Player1);
+        for col in 0..6 {
+            board.set_piece(Position::at(7, col), Disc::Player1);
+        }
+        board.set_piece(sq("g8"), Disc::Player2);
+        let mut game = GgfGame {
+            board,
+            ..GgfGame::default()
+        };
+        game.moves
+            .push(GgfMove::new(Ply::Move(Player::Player1, sq("a1"))));
+        game.moves.push(GgfMove::new(Ply::Pass(Player::Player2)));
+        game.moves
+            .push(GgfMove::new(Ply::Move(Player::Player1, sq("h8"))));
+        assert!(game.replay().is_ok());
+        assert_eq!(game.to_string().parse(), Ok(game.clone()));
+        // The pass may be left out
+        game.moves.remove(1);
+        assert!(game.replay().is_ok());
+        // But not played when there is a move
+        game.moves
+            .insert(0, GgfMove::new(Ply::Pass(Player::Player1)));
+        assert_eq!(game.replay(), Err(GgfError::IllegalPass { ply: 1 }));
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(
+            RECORD.replace("W[d3]", "W[d3]B[a1]").parse::<GgfGame>(),
+            Err(GgfError::IllegalMove {
+                ply: 5,
+                error: MoveError::NoFlips
+            })
+        );
+        assert_eq!(
+            RECORD.replace("W[d6", "B[d6").parse::<GgfGame>(),
+            Err(GgfError::IllegalMove {
+                ply: 2,
+                error: MoveError::NotYourTurn
+            })
+        );
+        assert_eq!(
+            RECORD.replace("W[d3]", "W[z9]").parse::<GgfGame>(),
+            Err(GgfError::InvalidTag {
+                tag: "W".to_string(),
+                value: "z9".to_string()
+            })
+        );
+        assert!(matches!(
+            RECORD.replace("BO[8", "BO[10").parse::<GgfGame>(),
+            Err(GgfError::UnsupportedBoard(_))
+        ));
+        assert!(matches!(
+            RECORD.replace("O*---", "O*--").parse::<GgfGame>(),
+            Err(GgfError::InvalidTag { .. })
+        ));
+        assert!(matches!(
+            RECORD.replace(";)", "").parse::<GgfGame>(),
+            Err(GgfError::Syntax { .. })
+        ));
+        assert!(matches!(
+            "no record".parse::<GgfGame>(),
+            Err(GgfError::Syntax { offset: 0, .. })
+        ));
+    }
+
+    #[test]
+    fn test_parse_all() {
+        let text = format!("{}\n{}\n", RECORD, RECORD.replace("W[d3]", ""));
+        let games = GgfGame::parse_all(&text).unwrap();
+        assert_eq!(games.len(), 2);
+        assert_eq!(games[0].moves.len(), 4);
+        assert_eq!(games[1].moves.len(), 3);
+    }
+}
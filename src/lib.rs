@@ -1,8 +1,26 @@
+pub mod ai;
 mod board;
+pub mod book;
+pub mod endgame;
+mod error;
+mod game;
+pub mod ggf;
+mod perft;
 mod position;
+mod rng;
+mod transcript;
+pub mod wthor;
 
-pub use board::Board;
-pub use position::Position;
+pub use board::{
+    Board, Board10, Board6, Direction, Disc, EmptyRegion, Iter, Move, Neighbours, ParseBoardError,
+    Player, PositionFeatures, Rules, SizedBoard, SizedMove, SizedPosition, SizedStrider, Strider,
+    Symmetry,
+};
+pub use error::MoveError;
+pub use game::{Game, Ply, Status};
+pub use perft::perft;
+pub use position::{ParsePositionError, Position, PositionError};
+pub use transcript::{Transcript, TranscriptError};
 
 #[cfg(test)]
 mod tests {
@@ -0,0 +1,63 @@
+use crate::board::{Player, Rules};
+
+/// Counts the leaf nodes of the game tree `depth` plies below `board`, with `player` to move.
+///
+/// A forced pass counts as a ply of its own, and a finished game counts as a single leaf no
+/// matter how much depth is left. Any board with [`Rules`] can be counted, such as a
+/// [`SizedBoard`](crate::SizedBoard).
+pub fn perft<B: Rules>(board: &B, player: Player, depth: u32) -> u64 {
+    if depth == 0 {
+        return 1;
+    }
+    let count = board.legal_move_count(player);
+    if count == 0 {
+        if board.legal_move_count(player.opponent()) == 0 {
+            return 1;
+        }
+        return perft(board, player.opponent(), depth - 1);
+    }
+    if depth == 1 {
+        return count as u64;
+    }
+    board
+        .legal_moves(player)
+        .into_iter()
+        .map(|pos| {
+            let mut child = board.clone();
+            child.place_piece(pos, player);
+            perft(&child, player.opponent(), depth - 1)
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::{Board, Disc};
+    use crate::position::Position;
+
+    #[test]
+    fn test_depth_zero() {
+        assert_eq!(perft(&Board::default(), Player::Player1, 0), 1);
+        assert_eq!(perft(&Board::empty(), Player::Player1, 0), 1);
+    }
+
+    #[test]
+    fn test_finished_game_is_a_leaf() {
+        assert_eq!(perft(&Board::empty(), Player::Player1, 5), 1);
+    }
+
+    #[test]
+    fn test_pass_counts_as_a_ply() {
+        // Player 2 has nothing to play, player 1 has a single move on c1
+        let mut board = Board::empty();
+        board.set_piece(Position::at(0, 0), Disc::Player1);
+        board.set_piece(Position::at(0, 1), Disc::Player2);
+        assert_eq!(perft(&board, Player::Player2, 1), 1);
+        assert_eq!(perft(&board, Player::Player2, 2), 1);
+        // After the pass and c1 only player 1 discs are left, so the game is over
+        let mut after = board.clone();
+        after.place_piece(Position::at(0, 2), Player::Player1);
+        assert_eq!(perft(&after, Player::Player2, 3), 1);
+    }
+}
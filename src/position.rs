@@ -1,6 +1,17 @@
+use crate::board::Symmetry;
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
 pub(crate) const MAX_VALID_POS: u8 = 0b00111111; // (7, 7)
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+/// A square of the board.
+///
+/// In standard notation columns `a` to `h` are columns 0 to 7 and rows `1` to `8` are rows 0
+/// to 7, so `a1` is index 0, `h1` index 7 and `h8` index 63. With the default board the
+/// start position then has white discs on `d4` and `e5`, as usual.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
 pub struct Position {
     /// Use a single bit for indexing
     ///
@@ -9,6 +20,42 @@ pub struct Position {
 }
 
 impl Position {
+    /// Returns the position at `(row, col)`, or `None` if either is outside `0..8`.
+    pub const fn new(row: usize, col: usize) -> Option<Self> {
+        if row > 7 || col > 7 {
+            return None;
+        }
+        Some(Self {
+            idx: ((row as u8) << 3) + col as u8,
+        })
+    }
+
+    /// Returns the position at `(row, col)`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if either `row` or `col` is outside `0..8`.
+    pub const fn at(row: usize, col: usize) -> Self {
+        match Self::new(row, col) {
+            Some(pos) => pos,
+            None => panic!("Index out of bounds"),
+        }
+    }
+
+    /// Returns the position with square index `idx` (`row * 8 + col`), or `None` if it is
+    /// not in `0..64`.
+    pub const fn from_index(idx: u8) -> Option<Self> {
+        if idx > MAX_VALID_POS {
+            return None;
+        }
+        Some(Self { idx })
+    }
+
+    /// The square index of this position, `row * 8 + col`.
+    pub fn index(&self) -> usize {
+        self.idx as usize
+    }
+
     pub fn row(&self) -> usize {
         ((self.idx >> 3) & 0b111) as usize
     }
@@ -16,26 +63,121 @@ impl Position {
     pub fn col(&self) -> usize {
         (self.idx & 0b111) as usize
     }
+
+    /// The square `sym` takes this one to, matching [`Board::transform`](crate::Board::transform).
+    pub fn transform(self, sym: Symmetry) -> Self {
+        let (row, col) = (self.row(), self.col());
+        let (row, col) = match sym {
+            Symmetry::Identity => (row, col),
+            Symmetry::Rotate90 => (col, 7 - row),
+            Symmetry::Rotate180 => (7 - row, 7 - col),
+            Symmetry::Rotate270 => (7 - col, row),
+            Symmetry::FlipVertical => (7 - row, col),
+            Symmetry::FlipHorizontal => (row, 7 - col),
+            Symmetry::FlipDiagonal => (col, row),
+            Symmetry::FlipAntiDiagonal => (7 - col, 7 - row),
+        };
+        Self::at(row, col)
+    }
 }
 
-impl From<(u8, u8)> for Position {
-    fn from(p: (u8, u8)) -> Self {
-        if p.0 > 7 || p.1 > 7 {
-            panic!("Index out of bounds")
-        }
-        Self {
-            idx: (p.0 << 3) + p.1,
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PositionError {
+    /// The row or column is outside `0..8`
+    OutOfBounds { row: usize, col: usize },
+    /// The square index is outside `0..64`
+    InvalidIndex(u8),
+}
+
+impl fmt::Display for PositionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PositionError::OutOfBounds { row, col } => {
+                write!(f, "position ({}, {}) is out of bounds", row, col)
+            }
+            PositionError::InvalidIndex(idx) => write!(f, "square index {} is out of bounds", idx),
         }
     }
 }
 
-impl From<(usize, usize)> for Position {
-    fn from(p: (usize, usize)) -> Self {
-        if p.0 > 7 || p.1 > 7 {
-            panic!("Index out of bounds")
+impl Error for PositionError {}
+
+/// Error from parsing a [`Position`] in standard notation
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ParsePositionError {
+    /// The string is not two characters long
+    Length(usize),
+    /// The column is not a letter from `a` to `h`
+    Column(char),
+    /// The row is not a digit from `1` to `8`
+    Row(char),
+}
+
+impl fmt::Display for ParsePositionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParsePositionError::Length(len) => {
+                write!(f, "expected a square like a1, got {} characters", len)
+            }
+            ParsePositionError::Column(c) => write!(f, "invalid column {:?}", c),
+            ParsePositionError::Row(c) => write!(f, "invalid row {:?}", c),
         }
-        let p = (p.0 as u8, p.1 as u8);
-        p.into()
+    }
+}
+
+impl Error for ParsePositionError {}
+
+impl fmt::Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}{}",
+            (b'a' + self.col() as u8) as char,
+            (b'1' + self.row() as u8) as char
+        )
+    }
+}
+
+impl FromStr for Position {
+    type Err = ParsePositionError;
+
+    /// Parses a square like `f5`, ignoring case.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut chars = s.chars();
+        let (col, row) = match (chars.next(), chars.next(), chars.next()) {
+            (Some(col), Some(row), None) => (col, row),
+            _ => return Err(ParsePositionError::Length(s.chars().count())),
+        };
+        let col_idx = match col.to_ascii_lowercase() {
+            c @ 'a'..='h' => c as usize - 'a' as usize,
+            _ => return Err(ParsePositionError::Column(col)),
+        };
+        let row_idx = match row {
+            '1'..='8' => row as usize - '1' as usize,
+            _ => return Err(ParsePositionError::Row(row)),
+        };
+        Ok(Self::at(row_idx, col_idx))
+    }
+}
+
+impl TryFrom<(u8, u8)> for Position {
+    type Error = PositionError;
+    fn try_from(p: (u8, u8)) -> Result<Self, Self::Error> {
+        Self::try_from((p.0 as usize, p.1 as usize))
+    }
+}
+
+impl TryFrom<(usize, usize)> for Position {
+    type Error = PositionError;
+    fn try_from(p: (usize, usize)) -> Result<Self, Self::Error> {
+        Self::new(p.0, p.1).ok_or(PositionError::OutOfBounds { row: p.0, col: p.1 })
+    }
+}
+
+impl TryFrom<u8> for Position {
+    type Error = PositionError;
+    fn try_from(idx: u8) -> Result<Self, Self::Error> {
+        Self::from_index(idx).ok_or(PositionError::InvalidIndex(idx))
     }
 }
 
@@ -48,22 +190,101 @@ mod tests {
         let pos: Position = Position { idx: 3 * 8 + 6 };
         assert_eq!(pos.row(), 3);
         assert_eq!(pos.col(), 6);
+        assert_eq!(pos.index(), 30);
     }
 
     #[test]
-    fn test_from_impl() {
-        let pos: Position = (5u8, 7u8).into();
+    fn test_constructors() {
+        let pos = Position::new(5, 7).unwrap();
         assert_eq!(pos.row(), 5);
-        assert_eq!(pos.col(), 6);
-        let pos: Position = (5usize, 7usize).into();
+        assert_eq!(pos.col(), 7);
+        assert_eq!(Position::at(5, 7), pos);
+        assert_eq!(Position::from_index(47), Some(pos));
+        assert_eq!(Position::new(8, 0), None);
+        assert_eq!(Position::new(0, 8), None);
+        assert_eq!(Position::from_index(64), None);
+    }
+
+    #[test]
+    fn test_try_from_impl() {
+        let pos = Position::try_from((5u8, 7u8)).unwrap();
         assert_eq!(pos.row(), 5);
-        assert_eq!(pos.col(), 6);
+        assert_eq!(pos.col(), 7);
+        let pos = Position::try_from((5usize, 7usize)).unwrap();
+        assert_eq!(pos.row(), 5);
+        assert_eq!(pos.col(), 7);
+        let pos = Position::try_from(47u8).unwrap();
+        assert_eq!(pos.row(), 5);
+        assert_eq!(pos.col(), 7);
+    }
+
+    #[test]
+    fn test_try_from_impl_out_of_bounds() {
+        assert_eq!(
+            Position::try_from((9u8, 20u8)),
+            Err(PositionError::OutOfBounds { row: 9, col: 20 })
+        );
+        assert_eq!(
+            Position::try_from((3usize, 8usize)),
+            Err(PositionError::OutOfBounds { row: 3, col: 8 })
+        );
+        assert_eq!(
+            Position::try_from(64u8),
+            Err(PositionError::InvalidIndex(64))
+        );
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(Position::at(0, 0).to_string(), "a1");
+        assert_eq!(Position::at(0, 7).to_string(), "h1");
+        assert_eq!(Position::at(4, 5).to_string(), "f5");
+        assert_eq!(Position::at(7, 7).to_string(), "h8");
+    }
+
+    #[test]
+    fn test_from_str() {
+        assert_eq!("a1".parse(), Ok(Position::at(0, 0)));
+        assert_eq!("F5".parse(), Ok(Position::at(4, 5)));
+        assert_eq!("h8".parse::<Position>().unwrap().index(), 63);
+        for idx in 0..64 {
+            let pos = Position::from_index(idx).unwrap();
+            assert_eq!(pos.to_string().parse(), Ok(pos));
+        }
+    }
+
+    #[test]
+    fn test_from_str_errors() {
+        assert_eq!("".parse::<Position>(), Err(ParsePositionError::Length(0)));
+        assert_eq!(
+            "a10".parse::<Position>(),
+            Err(ParsePositionError::Length(3))
+        );
+        assert_eq!(
+            "i1".parse::<Position>(),
+            Err(ParsePositionError::Column('i'))
+        );
+        assert_eq!("a9".parse::<Position>(), Err(ParsePositionError::Row('9')));
+        assert_eq!("a0".parse::<Position>(), Err(ParsePositionError::Row('0')));
+        assert_eq!(
+            "1a".parse::<Position>(),
+            Err(ParsePositionError::Column('1'))
+        );
+    }
+
+    #[test]
+    fn test_transform() {
+        let pos: Position = "b1".parse().unwrap();
+        let images: Vec<String> = Symmetry::ALL
+            .iter()
+            .map(|&sym| pos.transform(sym).to_string())
+            .collect();
+        assert_eq!(images, ["b1", "h2", "g8", "a7", "b8", "g1", "a2", "h7"]);
     }
 
     #[should_panic(expected = "Index out of bounds")]
     #[test]
-    fn test_from_impl_out_of_bounds() {
-        let _pos: Position = (9u8, 20u8).into();
-        let _pos: Position = (9usize, 20usize).into();
+    fn test_at_out_of_bounds() {
+        let _pos = Position::at(9, 20);
     }
 }
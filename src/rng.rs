@@ -0,0 +1,105 @@
+#[cfg(test)]
+use crate::board::{Board, Move, Player};
+#[cfg(test)]
+use crate::position::Position;
+
+/// Small xorshift generator, good enough for picking moves at random.
+#[derive(Debug, Clone)]
+pub(crate) struct Rng(u64);
+
+impl Rng {
+    pub(crate) fn new(seed: u64) -> Self {
+        // Xorshift gets stuck on a zero state
+        Self(if seed == 0 {
+            0x9e37_79b9_7f4a_7c15
+        } else {
+            seed
+        })
+    }
+
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// A number in `0..n`, which must not be empty.
+    pub(crate) fn below(&mut self, n: u64) -> u64 {
+        self.next_u64() % n
+    }
+
+    /// One of the set bits of `mask`, chosen uniformly, as a bit index.
+    pub(crate) fn pick_bit(&mut self, mut mask: u64) -> u32 {
+        for _ in 0..self.below(mask.count_ones() as u64) {
+            mask &= mask - 1;
+        }
+        mask.trailing_zeros()
+    }
+}
+
+/// A game of random moves from the start position, for tests.
+#[cfg(test)]
+pub(crate) struct RandomGame<'a> {
+    rng: &'a mut Rng,
+    pub(crate) board: Board,
+    /// The side to move, which may have to pass
+    pub(crate) player: Player,
+}
+
+#[cfg(test)]
+impl<'a> RandomGame<'a> {
+    pub(crate) fn new(rng: &'a mut Rng) -> Self {
+        Self {
+            rng,
+            board: Board::default(),
+            player: Player::Player1,
+        }
+    }
+
+    /// Plays a random move, passing first if the side to move has none, or returns `None` once
+    /// neither side can move.
+    pub(crate) fn play(&mut self) -> Option<Move> {
+        let mut player = self.player;
+        let mut moves = self.board.legal_moves_mask(player);
+        if moves == 0 {
+            player = player.opponent();
+            moves = self.board.legal_moves_mask(player);
+            if moves == 0 {
+                return None;
+            }
+        }
+        let idx = self.rng.pick_bit(moves) as u8;
+        let mv = self.board.place_piece(Position { idx }, player);
+        self.player = player.opponent();
+        Some(mv)
+    }
+}
+
+/// A random position with `empties` empty squares, or `None` if the game ended before.
+#[cfg(test)]
+pub(crate) fn random_position(rng: &mut Rng, empties: usize) -> Option<(Board, Player)> {
+    let mut game = RandomGame::new(rng);
+    while game.board.empty_squares().count_ones() as usize > empties {
+        game.play()?;
+    }
+    Some((game.board, game.player))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pick_bit() {
+        let mut rng = Rng::new(0);
+        let mask = 0x8000_0000_0001_0010u64;
+        let mut seen = 0u64;
+        for _ in 0..100 {
+            let bit = rng.pick_bit(mask);
+            assert_ne!(mask & 1 << bit, 0);
+            seen |= 1 << bit;
+        }
+        assert_eq!(seen, mask);
+    }
+}
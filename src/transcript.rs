@@ -0,0 +1,309 @@
+//! Move lists of games played from the start position.
+
+use crate::board::{Board, Player};
+use crate::error::MoveError;
+use crate::game::{Game, Ply};
+use crate::position::{ParsePositionError, Position};
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum TranscriptError {
+    /// The square starting at byte `offset` could not be parsed
+    Syntax {
+        offset: usize,
+        error: ParsePositionError,
+    },
+    /// Move number `number` (counted from 1, passes left out) is not legal
+    IllegalMove {
+        number: usize,
+        pos: Position,
+        error: MoveError,
+    },
+    /// The game did not start from the standard start position with player 1 to move
+    NonStandardStart,
+}
+
+impl fmt::Display for TranscriptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TranscriptError::Syntax { offset, error } => write!(f, "at {}: {}", offset, error),
+            TranscriptError::IllegalMove { number, pos, error } => {
+                write!(f, "move {} ({}): {}", number, pos, error)
+            }
+            TranscriptError::NonStandardStart => {
+                write!(f, "game does not start from the standard position")
+            }
+        }
+    }
+}
+
+impl Error for TranscriptError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            TranscriptError::Syntax { error, .. } => Some(error),
+            TranscriptError::IllegalMove { error, .. } => Some(error),
+            TranscriptError::NonStandardStart => None,
+        }
+    }
+}
+
+/// The plies of a game from the standard start position, passes included.
+///
+/// The text form is the usual concatenation of squares, e.g. `f5d6c3d3c4`. Passes are
+/// forced, so they are left out of it and filled back in when parsing.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Transcript {
+    /// The game after every ply, which keeps the plies and checks new moves
+    game: Game,
+}
+
+impl Transcript {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replays `moves` from the start position, checking that each of them is legal.
+    pub fn from_moves(moves: &[Position]) -> Result<Self, TranscriptError> {
+        let mut transcript = Self::new();
+        for &pos in moves {
+            transcript.push(pos)?;
+        }
+        Ok(transcript)
+    }
+
+    /// The transcript of a game, which must have been started from the standard start
+    /// position.
+    pub fn from_game(game: &Game) -> Result<Self, TranscriptError> {
+        if game.start() != (&Board::default(), Player::Player1) {
+            return Err(TranscriptError::NonStandardStart);
+        }
+        Self::from_moves(
+            &game
+                .history()
+                .iter()
+                .filter_map(ply_move)
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    /// Plays `pos` for the side to move, recording the forced pass that may follow.
+    pub fn push(&mut self, pos: Position) -> Result<(), TranscriptError> {
+        let number = self.moves().count() + 1;
+        self.game
+            .play(pos)
+            .map(|_| ())
+            .map_err(|error| TranscriptError::IllegalMove { number, pos, error })
+    }
+
+    pub fn plies(&self) -> &[Ply] {
+        self.game.history()
+    }
+
+    /// Number of plies, passes included.
+    pub fn len(&self) -> usize {
+        self.plies().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.plies().is_empty()
+    }
+
+    /// The squares played, without the passes.
+    pub fn moves(&self) -> impl Iterator<Item = Position> + '_ {
+        self.plies().iter().filter_map(ply_move)
+    }
+
+    /// The game after the last ply.
+    pub fn game(&self) -> &Game {
+        &self.game
+    }
+
+    /// The board and the side to move after the first `ply` plies, or `None` if there are
+    /// fewer plies than that.
+    pub fn position_at(&self, ply: usize) -> Option<(Board, Player)> {
+        if ply > self.len() {
+            return None;
+        }
+        let mut board = Board::default();
+        for &p in &self.plies()[..ply] {
+            if let Ply::Move(player, pos) = p {
+                board.place_piece(pos, player);
+            }
+        }
+        let to_move = match self.plies().get(ply) {
+            Some(&Ply::Move(player, _)) | Some(&Ply::Pass(player)) => player,
+            None => self.game.to_move(),
+        };
+        Some((board, to_move))
+    }
+}
+
+fn ply_move(ply: &Ply) -> Option<Position> {
+    match *ply {
+        Ply::Move(_, pos) => Some(pos),
+        Ply::Pass(_) => None,
+    }
+}
+
+impl fmt::Display for Transcript {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.moves().try_for_each(|pos| write!(f, "{}", pos))
+    }
+}
+
+impl FromStr for Transcript {
+    type Err = TranscriptError;
+
+    /// Parses concatenated squares, which may be separated by whitespace.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut transcript = Self::new();
+        let mut chars = s.char_indices().filter(|(_, c)| !c.is_whitespace());
+        while let Some((offset, col)) = chars.next() {
+            let square: String = std::iter::once(col)
+                .chain(chars.next().map(|(_, row)| row))
+                .collect();
+            let pos = square
+                .parse()
+                .map_err(|error| TranscriptError::Syntax { offset, error })?;
+            transcript.push(pos)?;
+        }
+        Ok(transcript)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::Status;
+
+    fn sq(s: &str) -> Position {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let transcript: Transcript = "f5d6c3d3c4".parse().unwrap();
+        assert_eq!(transcript.len(), 5);
+        assert_eq!(transcript.to_string(), "f5d6c3d3c4");
+        assert_eq!(transcript.plies()[1], Ply::Move(Player::Player2, sq("d6")));
+        assert_eq!("F5 d6\nC3d3 c4".parse(), Ok(transcript.clone()));
+        assert_eq!("".parse(), Ok(Transcript::new()));
+
+        let moves: Vec<_> = transcript.moves().collect();
+        assert_eq!(Transcript::from_moves(&moves), Ok(transcript.clone()));
+        assert_eq!(Transcript::from_game(transcript.game()), Ok(transcript));
+    }
+
+    #[test]
+    fn test_position_at() {
+        let transcript: Transcript = "f5d6c3".parse().unwrap();
+        assert_eq!(
+            transcript.position_at(0),
+            Some((Board::default(), Player::Player1))
+        );
+        let mut board = Board::default();
+        board.place_piece(sq("f5"), Player::Player1);
+        board.place_piece(sq("d6"), Player::Player2);
+        assert_eq!(
+            transcript.position_at(2),
+            Some((board.clone(), Player::Player1))
+        );
+        board.place_piece(sq("c3"), Player::Player1);
+        assert_eq!(transcript.position_at(3), Some((board, Player::Player2)));
+        assert_eq!(transcript.position_at(4), None);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            "f5d6 z3".parse::<Transcript>(),
+            Err(TranscriptError::Syntax {
+                offset: 5,
+                error: ParsePositionError::Column('z')
+            })
+        );
+        assert_eq!(
+            "f5d".parse::<Transcript>(),
+            Err(TranscriptError::Syntax {
+                offset: 2,
+                error: ParsePositionError::Length(1)
+            })
+        );
+        assert_eq!(
+            "f5d6f5".parse::<Transcript>(),
+            Err(TranscriptError::IllegalMove {
+                number: 3,
+                pos: sq("f5"),
+                error: MoveError::Occupied
+            })
+        );
+        assert_eq!(
+            "f5a1".parse::<Transcript>(),
+            Err(TranscriptError::IllegalMove {
+                number: 2,
+                pos: sq("a1"),
+                error: MoveError::NoFlips
+            })
+        );
+    }
+
+    #[test]
+    fn test_passes_and_game_end() {
+        // The shortest possible game: black wipes out white in nine moves
+        let transcript: Transcript = "d3c3b3d2e1d6d7e3f4".parse().unwrap();
+        assert!(matches!(
+            transcript.game().status(),
+            Status::Finished {
+                winner: Some(Player::Player1),
+                ..
+            }
+        ));
+        assert_eq!(
+            transcript.to_string().parse::<Transcript>().unwrap().len(),
+            9
+        );
+        let mut longer = transcript.clone();
+        assert_eq!(
+            longer.push(sq("a1")),
+            Err(TranscriptError::IllegalMove {
+                number: 10,
+                pos: sq("a1"),
+                error: MoveError::GameOver
+            })
+        );
+
+        // Replaying a game with a forced pass fills it back in
+        let mut game = Game::new();
+        while !game.is_over() {
+            let pos = game.legal_moves().last().unwrap();
+            game.play(pos).unwrap();
+        }
+        let transcript = Transcript::from_game(&game).unwrap();
+        assert!(transcript
+            .plies()
+            .iter()
+            .any(|ply| matches!(ply, Ply::Pass(_))));
+        assert_eq!(transcript.plies(), game.history());
+        assert_eq!(transcript.to_string().parse(), Ok(transcript.clone()));
+        let (board, _) = transcript.position_at(transcript.len()).unwrap();
+        assert_eq!(&board, game.board());
+    }
+
+    #[test]
+    fn test_from_game_with_other_start() {
+        let game = Game::from_board(Board::default(), Player::Player2);
+        assert_eq!(
+            Transcript::from_game(&game),
+            Err(TranscriptError::NonStandardStart)
+        );
+        let mut board = Board::default();
+        board.place_piece(sq("f5"), Player::Player1);
+        let game = Game::from_board(board, Player::Player2);
+        assert_eq!(
+            Transcript::from_game(&game),
+            Err(TranscriptError::NonStandardStart)
+        );
+    }
+}
@@ -0,0 +1,363 @@
+//! Streaming reader for the WTHOR game databases of the French Othello Federation.
+//!
+//! A `.wtb` file is a 16-byte header followed by 68-byte game records. All numbers are
+//! little-endian. Each record holds the tournament and player numbers, the number of black
+//! discs at the end of the game and with perfect play, and up to 60 moves, one byte each,
+//! written as `10 * row + col` with both counted from 1. Passes are not recorded.
+
+use crate::board::{Board, Player};
+use crate::error::MoveError;
+use crate::position::Position;
+use std::error::Error;
+use std::fmt;
+use std::io::{self, Read};
+
+const HEADER_LEN: usize = 16;
+const RECORD_LEN: usize = 68;
+
+#[derive(Debug)]
+pub enum WthorError {
+    Io(io::Error),
+    /// Only 8x8 databases can be read
+    UnsupportedBoard(u8),
+    /// Move number `ply` (counted from 1) of game number `game` (counted from 0) is not a
+    /// square
+    InvalidSquare {
+        game: usize,
+        ply: usize,
+        byte: u8,
+    },
+    /// Move number `ply` (counted from 1) of game number `game` (counted from 0) is not legal
+    IllegalMove {
+        game: usize,
+        ply: usize,
+        error: MoveError,
+    },
+}
+
+impl fmt::Display for WthorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WthorError::Io(err) => write!(f, "{}", err),
+            WthorError::UnsupportedBoard(size) => write!(f, "unsupported board size {}", size),
+            WthorError::InvalidSquare { game, ply, byte } => {
+                write!(f, "game {}, move {}: invalid square {}", game, ply, byte)
+            }
+            WthorError::IllegalMove { game, ply, error } => {
+                write!(f, "game {}, move {}: {}", game, ply, error)
+            }
+        }
+    }
+}
+
+impl Error for WthorError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            WthorError::Io(err) => Some(err),
+            WthorError::IllegalMove { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for WthorError {
+    fn from(err: io::Error) -> Self {
+        WthorError::Io(err)
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct WthorHeader {
+    /// Date the file was written, as year, month and day
+    pub created: (u16, u8, u8),
+    /// Number of game records
+    pub games: u32,
+    /// Number of records in player and tournament files, unused in game files
+    pub records: u16,
+    /// Year the games were played
+    pub year: u16,
+    pub board_size: u8,
+    /// Whether the file holds solitaires rather than games
+    pub solitaire: bool,
+    /// Number of empty squares from which `theoretical_score` is computed
+    pub depth: u8,
+}
+
+impl WthorHeader {
+    fn decode(bytes: &[u8; HEADER_LEN]) -> Self {
+        Self {
+            created: (bytes[0] as u16 * 100 + bytes[1] as u16, bytes[2], bytes[3]),
+            games: u32::from_le_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]),
+            records: u16::from_le_bytes([bytes[8], bytes[9]]),
+            year: u16::from_le_bytes([bytes[10], bytes[11]]),
+            board_size: bytes[12],
+            solitaire: bytes[13] == 1,
+            depth: bytes[14],
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WthorGame {
+    /// Index into the tournament file
+    pub tournament: u16,
+    /// Indices into the player file
+    pub black: u16,
+    pub white: u16,
+    /// Number of black discs at the end of the game, empty squares going to the winner
+    pub actual_score: u8,
+    /// Number of black discs at the end of the game with perfect play from
+    /// [`depth`](WthorHeader::depth) empty squares on
+    pub theoretical_score: u8,
+    /// The moves from the standard start position, without passes
+    pub moves: Vec<Position>,
+}
+
+impl WthorGame {
+    /// Decodes game number `index`, replaying its moves to check that they are legal.
+    fn decode(bytes: &[u8; RECORD_LEN], index: usize) -> Result<Self, WthorError> {
+        let mut moves = vec![];
+        let mut board = Board::default();
+        let mut player = Player::Player1;
+        for (i, &byte) in bytes[8..].iter().enumerate() {
+            if byte == 0 {
+                break;
+            }
+            let ply = i + 1;
+            let (row, col) = ((byte / 10) as usize, (byte % 10) as usize);
+            if !(1..=8).contains(&row) || !(1..=8).contains(&col) {
+                return Err(WthorError::InvalidSquare {
+                    game: index,
+                    ply,
+                    byte,
+                });
+            }
+            let pos = Position::at(row - 1, col - 1);
+            if board.legal_moves_mask(player) == 0 {
+                player = player.opponent();
+            }
+            board
+                .try_place_piece(pos, player)
+                .map_err(|error| WthorError::IllegalMove {
+                    game: index,
+                    ply,
+                    error,
+                })?;
+            player = player.opponent();
+            moves.push(pos);
+        }
+        Ok(Self {
+            tournament: u16::from_le_bytes([bytes[0], bytes[1]]),
+            black: u16::from_le_bytes([bytes[2], bytes[3]]),
+            white: u16::from_le_bytes([bytes[4], bytes[5]]),
+            actual_score: bytes[6],
+            theoretical_score: bytes[7],
+            moves,
+        })
+    }
+
+    /// The board after the last move.
+    pub fn board(&self) -> Board {
+        let mut board = Board::default();
+        let mut player = Player::Player1;
+        for &pos in &self.moves {
+            if board.legal_moves_mask(player) == 0 {
+                player = player.opponent();
+            }
+            board.place_piece(pos, player);
+            player = player.opponent();
+        }
+        board
+    }
+}
+
+/// Reads the games of a WTHOR file one record at a time.
+#[derive(Debug)]
+pub struct WthorReader<R> {
+    reader: R,
+    header: WthorHeader,
+    /// Number of records read so far
+    read: usize,
+}
+
+impl<R: Read> WthorReader<R> {
+    /// Reads the header, failing if the file is not for 8x8 boards.
+    pub fn new(mut reader: R) -> Result<Self, WthorError> {
+        let mut bytes = [0; HEADER_LEN];
+        reader.read_exact(&mut bytes)?;
+        let header = WthorHeader::decode(&bytes);
+        // Older files leave the board size at 0 for 8x8
+        if header.board_size != 0 && header.board_size != 8 {
+            return Err(WthorError::UnsupportedBoard(header.board_size));
+        }
+        Ok(Self {
+            reader,
+            header,
+            read: 0,
+        })
+    }
+
+    pub fn header(&self) -> &WthorHeader {
+        &self.header
+    }
+}
+
+impl<R: Read> Iterator for WthorReader<R> {
+    type Item = Result<WthorGame, WthorError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.read >= self.header.games as usize {
+            return None;
+        }
+        let mut bytes = [0; RECORD_LEN];
+        if let Err(err) = self.reader.read_exact(&mut bytes) {
+            // Stop after a broken record rather than reading garbage
+            self.read = self.header.games as usize;
+            return Some(Err(err.into()));
+        }
+        self.read += 1;
+        Some(WthorGame::decode(&bytes, self.read - 1))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let left = self.header.games as usize - self.read;
+        (0, Some(left))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transcript::Transcript;
+
+    fn header(games: u32) -> Vec<u8> {
+        let mut bytes = vec![20, 24, 3, 15];
+        bytes.extend_from_slice(&games.to_le_bytes());
+        bytes.extend_from_slice(&0u16.to_le_bytes());
+        bytes.extend_from_slice(&2023u16.to_le_bytes());
+        bytes.extend_from_slice(&[8, 0, 22, 0]);
+        bytes
+    }
+
+    fn record(moves: &str, actual: u8, theoretical: u8) -> Vec<u8> {
+        let mut bytes = vec![];
+        bytes.extend_from_slice(&7u16.to_le_bytes());
+        bytes.extend_from_slice(&1234u16.to_le_bytes());
+        bytes.extend_from_slice(&42u16.to_le_bytes());
+        bytes.push(actual);
+        bytes.push(theoretical);
+        let transcript: Transcript = moves.parse().unwrap();
+        for pos in transcript.moves() {
+            bytes.push((10 * (pos.row() + 1) + pos.col() + 1) as u8);
+        }
+        bytes.resize(RECORD_LEN, 0);
+        bytes
+    }
+
+    #[test]
+    fn test_read() {
+        let mut file = header(2);
+        file.extend(record("f5d6c3d3c4", 40, 36));
+        file.extend(record("d3c3b3d2e1d6d7e3f4", 64, 64));
+        let mut reader = WthorReader::new(&file[..]).unwrap();
+        assert_eq!(
+            *reader.header(),
+            WthorHeader {
+                created: (2024, 3, 15),
+                games: 2,
+                records: 0,
+                year: 2023,
+                board_size: 8,
+                solitaire: false,
+                depth: 22,
+            }
+        );
+        assert_eq!(reader.size_hint(), (0, Some(2)));
+
+        let game = reader.next().unwrap().unwrap();
+        assert_eq!((game.tournament, game.black, game.white), (7, 1234, 42));
+        assert_eq!((game.actual_score, game.theoretical_score), (40, 36));
+        let transcript: Transcript = "f5d6c3d3c4".parse().unwrap();
+        assert_eq!(game.moves, transcript.moves().collect::<Vec<_>>());
+        assert_eq!(game.board(), transcript.position_at(5).unwrap().0);
+
+        let game = reader.next().unwrap().unwrap();
+        assert_eq!(game.moves.len(), 9);
+        assert_eq!(game.board().player1_count(), 13);
+        assert!(reader.next().is_none());
+    }
+
+    #[test]
+    fn test_passes() {
+        // A full game with passes, which the records leave out
+        let mut board = Board::default();
+        let mut player = Player::Player1;
+        let mut moves = String::new();
+        loop {
+            if board.legal_moves_mask(player) == 0 {
+                player = player.opponent();
+                if board.legal_moves_mask(player) == 0 {
+                    break;
+                }
+            }
+            let pos = board.all_legal_moves(player).last().unwrap();
+            board.place_piece(pos, player);
+            moves.push_str(&pos.to_string());
+            player = player.opponent();
+        }
+        let mut file = header(1);
+        file.extend(record(&moves, 0, 0));
+        let game = WthorReader::new(&file[..])
+            .unwrap()
+            .next()
+            .unwrap()
+            .unwrap();
+        assert_eq!(game.board(), board);
+    }
+
+    #[test]
+    fn test_errors() {
+        let mut file = header(3);
+        file.extend(record("f5d6", 0, 0));
+        let mut bad = record("f5d6", 0, 0);
+        bad[10] = 11;
+        file.extend(bad);
+        let mut reader = WthorReader::new(&file[..]).unwrap();
+        assert!(reader.next().unwrap().is_ok());
+        match reader.next() {
+            Some(Err(WthorError::IllegalMove {
+                game: 1,
+                ply: 3,
+                error: MoveError::NoFlips,
+            })) => (),
+            other => panic!("unexpected {:?}", other),
+        }
+        // The third record is missing
+        assert!(matches!(reader.next(), Some(Err(WthorError::Io(_)))));
+        assert!(reader.next().is_none());
+
+        let mut file = header(1);
+        let mut bad = record("f5", 0, 0);
+        bad[9] = 90;
+        file.extend(bad);
+        assert!(matches!(
+            WthorReader::new(&file[..]).unwrap().next(),
+            Some(Err(WthorError::InvalidSquare {
+                game: 0,
+                ply: 2,
+                byte: 90
+            }))
+        ));
+
+        let mut file = header(0);
+        file[12] = 10;
+        assert!(matches!(
+            WthorReader::new(&file[..]),
+            Err(WthorError::UnsupportedBoard(10))
+        ));
+        assert!(matches!(
+            WthorReader::new(&file[..10]),
+            Err(WthorError::Io(_))
+        ));
+    }
+}
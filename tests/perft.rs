@@ -0,0 +1,58 @@
+use libreversi::{perft, Board, Player};
+
+/// Leaf counts from the standard start position, with passes counted as plies
+const START_POSITION: [u64; 11] = [
+    1, 4, 12, 56, 244, 1396, 8200, 55092, 390216, 3005288, 24571284,
+];
+
+fn check(depth: usize) {
+    assert_eq!(
+        perft(&Board::default(), Player::Player1, depth as u32),
+        START_POSITION[depth],
+        "perft({}) from the start position",
+        depth
+    );
+}
+
+#[test]
+fn perft_shallow() {
+    (0..=6).for_each(check);
+}
+
+#[test]
+fn perft_7() {
+    check(7);
+}
+
+#[test]
+fn perft_8() {
+    check(8);
+}
+
+#[test]
+#[ignore]
+fn perft_9() {
+    check(9);
+}
+
+#[test]
+#[ignore]
+fn perft_10() {
+    check(10);
+}
+
+#[test]
+fn perft_is_symmetric_in_the_first_move() {
+    let board = Board::default();
+    let counts: Vec<u64> = board
+        .all_legal_moves(Player::Player1)
+        .map(|pos| {
+            let mut child = board.clone();
+            child.place_piece(pos, Player::Player1);
+            perft(&child, Player::Player2, 5)
+        })
+        .collect();
+    assert_eq!(counts.len(), 4);
+    assert!(counts.iter().all(|&c| c == counts[0]));
+    assert_eq!(counts.iter().sum::<u64>(), START_POSITION[6]);
+}
@@ -0,0 +1,75 @@
+use libreversi::{
+    perft, Board, Board10, Board6, Direction, Disc, Game, Iter, Move, MoveError, Neighbours,
+    Player, Ply, Position, SizedMove, SizedPosition, Status, Strider,
+};
+
+#[test]
+fn discs_and_players_are_nameable() {
+    let board = Board::default();
+    let player = Player::Player1;
+    assert_eq!(player.opponent(), Player::Player2);
+    assert_eq!(Disc::from(player), Disc::Player1);
+    assert!(board.is_legal_move(Position::at(2, 3), player));
+    match board.get_piece(Position::at(3, 3)) {
+        Disc::Player2 => (),
+        other => panic!("unexpected disc {:?}", other),
+    }
+}
+
+#[test]
+fn legal_moves_and_placement() {
+    let mut board = Board::default();
+    let moves: Vec<Position> = board.all_legal_moves(Player::Player1).collect();
+    assert_eq!(moves.len(), 4);
+    let mv: Move = board.place_piece(moves[0], Player::Player1);
+    assert_eq!(mv.flipped().collect::<Vec<_>>(), vec![Position::at(3, 3)]);
+    assert_eq!(board.player1_count(), 4);
+    assert_eq!(board.player2_count(), 1);
+    board.unmake(&mv);
+    assert_eq!(board, Board::default());
+}
+
+#[test]
+fn iterators_are_nameable() {
+    let board = Board::default();
+    let iter: Iter = board.iter();
+    assert_eq!(iter.filter(|&d| d != Disc::Empty).count(), 4);
+
+    let neighbours: Neighbours = board.neighbours(Position::at(0, 0));
+    assert_eq!(neighbours.count(), 3);
+
+    let strider: Strider = board.get_points_in_line(Position::at(3, 4), Position::at(4, 4));
+    assert_eq!(strider.dir(), Direction::Down);
+    assert_eq!(strider.pos(), Position::at(3, 4));
+    let line: Vec<Disc> = strider.map(|(_, d)| d).collect();
+    assert_eq!(
+        line,
+        vec![Disc::Player2, Disc::Empty, Disc::Empty, Disc::Empty]
+    );
+}
+
+#[test]
+fn game_drives_turns() {
+    let mut game = Game::new();
+    assert_eq!(game.status(), Status::InProgress);
+    let mv = game.play(Position::at(2, 3)).unwrap();
+    assert_eq!(game.play(Position::at(2, 3)), Err(MoveError::Occupied));
+    assert_eq!(mv.flip_count(), 1);
+    assert_eq!(game.to_move(), Player::Player2);
+    assert_eq!(
+        game.history(),
+        &[Ply::Move(Player::Player1, Position::at(2, 3))]
+    );
+}
+
+#[test]
+fn sized_boards_play_through_game() {
+    let mut game: Game<Board10> = Game::default();
+    let pos: SizedPosition<10> = "e4".parse().unwrap();
+    let mv: SizedMove<10> = game.play(pos).unwrap();
+    assert_eq!(mv.flip_count(), 1);
+    assert_eq!(game.history(), &[Ply::Move(Player::Player1, pos)]);
+    assert_eq!(game.board()[pos], Disc::Player1);
+    // The edges of a 6x6 board only come into play after a few plies
+    assert_eq!(perft(&Board6::default(), Player::Player1, 3), 56);
+}